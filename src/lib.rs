@@ -2,4 +2,4 @@
 
 pub mod modules;
 
-pub use modules::{atom, config, error, html, qiita_api, state};
+pub use modules::{atom, canonical, compress, config, curation, digest, entry_id, error, extra_feed, fingerprint, fsio, history, html, inspect, json_feed, lock, merge, milestone, prune, qiita_api, rss, schema, state, storage, tag_feed, tier};
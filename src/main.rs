@@ -1,18 +1,126 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use chrono::{DateTime, FixedOffset, Utc};
-use qiita_high_likes_rss::atom::{build_feed_xml, default_feed_updated, FeedEntry, FeedInfo};
-use qiita_high_likes_rss::config::AppConfig;
+use qiita_high_likes_rss::atom::{
+    build_feed_xml, default_feed_updated, EntrySource, FeedAuthor, FeedEntry, FeedInfo, QiitaMeta,
+};
+use qiita_high_likes_rss::canonical::extract_username;
+use qiita_high_likes_rss::compress::{write_precompressed, Precompress};
+use qiita_high_likes_rss::config::{AppConfig, EntryUpdated, FeedSort, LikesCountMode};
+use qiita_high_likes_rss::curation::{CuratedItem, Curation};
+use qiita_high_likes_rss::entry_id::{stable_entry_id, FIXED_ID_DATE};
 use qiita_high_likes_rss::error::AppError;
-use qiita_high_likes_rss::html::{build_index_html, IndexPage};
+use qiita_high_likes_rss::extra_feed::ExtraFeed;
+use qiita_high_likes_rss::fsio::write_atomic;
+use qiita_high_likes_rss::html::{build_index_html, escape_html, FeedLink, IndexPage};
+use qiita_high_likes_rss::inspect::{compute_stats, export_csv, list_items, ListFilter, ListSort};
+use qiita_high_likes_rss::json_feed::build_json_feed;
 use qiita_high_likes_rss::qiita_api::QiitaClient;
-use qiita_high_likes_rss::state::{select_updated_time, StateStore, StoredItem};
+use qiita_high_likes_rss::rss::build_rss_xml;
+use qiita_high_likes_rss::state::{
+    item_key, select_updated_time, StateStore, StoredItem, DEFAULT_STATE_BACKUPS,
+};
+use qiita_high_likes_rss::storage::migrate_state;
 use std::cmp::Ordering;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
-#[command(name = "qiita-feed", version)]
+#[command(
+    name = "qiita-feed",
+    version,
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    run: Option<RunArgs>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// state を別のストレージ形式 (JSON / JSON Lines / SQLite) へ移行する
+    MigrateState {
+        #[arg(long)]
+        from: PathBuf,
+        #[arg(long)]
+        to: PathBuf,
+        #[command(flatten)]
+        lock: LockArgs,
+    },
+    /// state の確認と手入れ
+    State(StateArgs),
+}
+
+#[derive(Args, Debug)]
+struct StateArgs {
+    #[arg(long)]
+    state: PathBuf,
+    /// 保存時に残すバックアップの世代数 (state_backups) を読む config
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// 結果を JSON で出力する
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(flatten)]
+    lock: LockArgs,
+    #[command(subcommand)]
+    action: StateAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum StateAction {
+    /// 保存済み item を一覧表示する
+    List {
+        #[arg(long, value_enum, default_value_t = SortArg::Likes)]
+        sort: SortArg,
+        #[arg(long = "min-likes")]
+        min_likes: Option<u32>,
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        author: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// item を 1 件表示する (key / item_id / link で指定)
+    Show { item_id: String },
+    /// item を削除する
+    Remove { item_id: String },
+    /// b を a に統合して b を削除する
+    Merge { a: String, b: String },
+    /// item を pinned にして prune で消えないようにする
+    Pin { item_id: String },
+    /// item の pinned を外す
+    Unpin { item_id: String },
+    /// 保存済み item を書き出す
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+    },
+    /// 件数や likes の集計を表示する
+    Stats,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SortArg {
+    Likes,
+    Published,
+    Qualified,
+    FirstSeen,
+    LastSeen,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
     #[arg(long)]
     config: PathBuf,
     #[arg(long)]
@@ -25,6 +133,23 @@ struct Cli {
     last_build: PathBuf,
     #[arg(long)]
     dry_run: bool,
+    /// 他のプロセスが state を使用中の場合に待つ秒数 (0 なら即終了)
+    // Option で flatten される RunArgs に LockArgs を入れ子にすると clap が値を埋めないため直接持つ
+    #[arg(long = "lock-timeout", default_value_t = 0)]
+    lock_timeout: u64,
+}
+
+#[derive(Args, Debug)]
+struct LockArgs {
+    /// 他のプロセスが state を使用中の場合に待つ秒数 (0 なら即終了)
+    #[arg(long = "lock-timeout", default_value_t = 0)]
+    lock_timeout: u64,
+}
+
+impl LockArgs {
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.lock_timeout)
+    }
 }
 
 fn main() {
@@ -36,46 +161,298 @@ fn main() {
 
 fn run() -> Result<(), AppError> {
     let cli = Cli::parse();
+    match (cli.command, cli.run) {
+        (Some(Command::MigrateState { from, to, lock }), _) => {
+            let timeout = lock.timeout();
+            let _from_lock = StateStore::lock(&from, timeout)?;
+            let _to_lock = StateStore::lock(&to, timeout)?;
+            let migrated = migrate_state(&from, &to)?;
+            println!(
+                "state を移行しました: {} -> {} items={}",
+                from.display(),
+                to.display(),
+                migrated
+            );
+            Ok(())
+        }
+        (Some(Command::State(args)), _) => run_state(args),
+        (None, Some(args)) => run_feed(args),
+        (None, None) => Err(AppError::config("実行に必要な引数が不足しています")),
+    }
+}
+
+fn run_state(args: StateArgs) -> Result<(), AppError> {
+    // 存在しない state を空として表示したり、ロックファイルを作ったりしない
+    if !args.state.exists() {
+        return Err(AppError::state(format!(
+            "state が見つかりません: {}",
+            args.state.display()
+        )));
+    }
+    // 確認だけのコマンドはロックを取らない
+    let _lock = if args.action.modifies_state() {
+        Some(StateStore::lock(&args.state, args.lock.timeout())?)
+    } else {
+        None
+    };
+    let backups = match &args.config {
+        Some(path) => AppConfig::load(path)?.state_backups,
+        None => DEFAULT_STATE_BACKUPS,
+    };
+    let mut state = StateStore::load(&args.state)?;
+    match args.action {
+        StateAction::List {
+            sort,
+            min_likes,
+            tag,
+            author,
+            limit,
+        } => {
+            let filter = ListFilter {
+                min_likes,
+                tag,
+                author,
+                limit,
+            };
+            let items = list_items(&state, &filter, sort.into());
+            if args.json {
+                return print_json(&items);
+            }
+            for item in items {
+                let likes = if item.likes_lower_bound {
+                    format!("≥{}", item.likes_count)
+                } else {
+                    item.likes_count.to_string()
+                };
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    likes,
+                    item.key,
+                    item.published.as_deref().unwrap_or("-"),
+                    item.title
+                );
+            }
+            Ok(())
+        }
+        StateAction::Show { item_id } => {
+            let key = find_state_key(&state, &item_id)?;
+            let item = &state.items[&key];
+            if args.json {
+                return print_json(item);
+            }
+            let yaml = serde_yaml::to_string(item)
+                .map_err(|e| AppError::feed(format!("item 出力失敗: {}", e)))?;
+            print!("{}", yaml);
+            Ok(())
+        }
+        StateAction::Remove { item_id } => {
+            let key = find_state_key(&state, &item_id)?;
+            let removed = state.remove_item(&key);
+            state.save_with_backups(&args.state, backups)?;
+            if args.json {
+                return print_json(&removed);
+            }
+            println!("removed: {}", key);
+            Ok(())
+        }
+        StateAction::Merge { a, b } => {
+            let keep = find_state_key(&state, &a)?;
+            let remove = find_state_key(&state, &b)?;
+            let merged = state.merge_items(&keep, &remove, Utc::now())?.clone();
+            state.save_with_backups(&args.state, backups)?;
+            if args.json {
+                return print_json(&merged);
+            }
+            println!("merged: {} <- {}", keep, remove);
+            Ok(())
+        }
+        StateAction::Pin { item_id } => {
+            set_state_pinned(&args.state, backups, args.json, &mut state, &item_id, true)
+        }
+        StateAction::Unpin { item_id } => {
+            set_state_pinned(&args.state, backups, args.json, &mut state, &item_id, false)
+        }
+        StateAction::Export { format } => {
+            let items = list_items(&state, &ListFilter::default(), ListSort::Likes);
+            if args.json || format == ExportFormat::Json {
+                return print_json(&items);
+            }
+            print!("{}", export_csv(&items));
+            Ok(())
+        }
+        StateAction::Stats => {
+            let stats = compute_stats(&state);
+            if args.json {
+                return print_json(&stats);
+            }
+            println!("items: {}", stats.items);
+            println!("watchlist: {}", stats.watchlist);
+            println!("likes_total: {}", stats.likes_total);
+            println!("likes_max: {}", stats.likes_max);
+            println!("likes_median: {}", stats.likes_median);
+            println!("lower_bound_items: {}", stats.lower_bound_items);
+            println!(
+                "published: {} .. {}",
+                stats.oldest_published.as_deref().unwrap_or("-"),
+                stats.newest_published.as_deref().unwrap_or("-")
+            );
+            println!(
+                "oldest_last_seen: {}",
+                stats.oldest_last_seen.as_deref().unwrap_or("-")
+            );
+            for tag in &stats.top_tags {
+                println!("tag: {} {}", tag.tag, tag.count);
+            }
+            Ok(())
+        }
+    }
+}
+
+impl StateAction {
+    fn modifies_state(&self) -> bool {
+        matches!(
+            self,
+            StateAction::Remove { .. }
+                | StateAction::Merge { .. }
+                | StateAction::Pin { .. }
+                | StateAction::Unpin { .. }
+        )
+    }
+}
+
+impl From<SortArg> for ListSort {
+    fn from(value: SortArg) -> Self {
+        match value {
+            SortArg::Likes => ListSort::Likes,
+            SortArg::Published => ListSort::Published,
+            SortArg::Qualified => ListSort::Qualified,
+            SortArg::FirstSeen => ListSort::FirstSeen,
+            SortArg::LastSeen => ListSort::LastSeen,
+        }
+    }
+}
+
+fn set_state_pinned(
+    path: &Path,
+    backups: usize,
+    json: bool,
+    state: &mut StateStore,
+    item_id: &str,
+    pinned: bool,
+) -> Result<(), AppError> {
+    let key = find_state_key(state, item_id)?;
+    state.set_pinned(&key, pinned);
+    state.save_with_backups(path, backups)?;
+    if json {
+        return print_json(&state.items[&key]);
+    }
+    println!("{}: {}", if pinned { "pinned" } else { "unpinned" }, key);
+    Ok(())
+}
+
+fn find_state_key(state: &StateStore, id: &str) -> Result<String, AppError> {
+    state
+        .find_key(id)
+        .ok_or_else(|| AppError::config(format!("item が見つかりません: {}", id)))
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::feed(format!("JSON 出力失敗: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn run_feed(cli: RunArgs) -> Result<(), AppError> {
     let config = AppConfig::load(&cli.config)?;
+    let curation = Curation::load_for_config(&cli.config, config.curation_path.as_deref())?;
     let now = Utc::now();
 
+    // 同じ state を使う別プロセスと load/merge/save が交錯しないようにする
+    let _lock = StateStore::lock(&cli.state, Duration::from_secs(cli.lock_timeout))?;
     let mut state = StateStore::load(&cli.state)?;
+    state.merge_policy = config.merge_policy.clone();
+    state.milestones = config
+        .milestone_feed
+        .as_ref()
+        .map(|feed| feed.thresholds.clone())
+        .unwrap_or_default();
     let client = QiitaClient::new(config.qiita_api_token.clone())?;
 
     // 人気 feed を取得して likes を付与する
     let mut feed_items = client.fetch_feed(&config.feed_source)?;
     let mut enriched = Vec::new();
+    let mut below = Vec::new();
     for item in feed_items.iter_mut() {
         let Some(item_id) = item.item_id.clone() else {
             eprintln!("item_id が取得できないためスキップ: title={}", item.title);
             continue;
         };
-        let likes = client.fetch_likes_count(
+        let exact = match config.likes_count_mode {
+            LikesCountMode::Exact => true,
+            LikesCountMode::Threshold => {
+                state.exact_recount_due(&item_id, now, config.likes_exact_recount_hours)
+            }
+        };
+        let threshold = if exact { None } else { Some(config.min_likes) };
+        let likes = client.fetch_likes(
             &item_id,
             config.likes_per_page,
             config.likes_max_pages,
+            threshold,
         )?;
-        item.likes_count = likes;
-        if likes >= config.min_likes {
+        item.likes_count = likes.count;
+        item.likes_lower_bound = likes.lower_bound;
+        item.likes_full_scan = likes.full_scan;
+        if likes.count >= config.min_likes {
+            if config.fetch_stocks {
+                item.stocks_count = Some(client.fetch_stocks_count(&item_id)?);
+            }
             enriched.push(item.clone());
+        } else {
+            below.push(item.clone());
         }
     }
 
-    let merged = state.merge_from_feed(&enriched, now);
-    // 実行間で保持するデータを整理する
-    state.prune(now, config.max_stored_days, config.max_stored_items);
+    let reports = state.merge_from_feed(&enriched, now);
+    let merged = reports.len();
+    let feed_keys: HashSet<String> = feed_items.iter().filter_map(item_key).collect();
+    let mut promoted = 0;
+    if config.watchlist_days > 0 {
+        // しきい値未満の候補を見守り、達したものを feed 側へ移す
+        state.watch_from_feed(&below, now);
+        recheck_watchlist(&client, &mut state, &config, &feed_keys, now);
+        promoted = state.promote_watchlist(config.min_likes, now);
+    }
+    // feed から外れた item の likes を予算の範囲で更新する
+    let refreshed = refresh_stored_likes(&client, &mut state, &config, &feed_keys, now);
+    // 実行間で保持するデータを整理する。curation の pin も消さない
+    state.pinned_keys = curation.pinned_keys(state.items.values());
+    let prune_report = state.prune_with_rules(
+        &config.effective_prune_rules(),
+        config.max_stored_items,
+        now,
+    );
+    state.prune_watchlist(now, config.watchlist_days);
+    // 一度配信した entry の id は以後変えない
+    state.assign_entry_ids(config.entry_id, now);
 
-    let mut items: Vec<StoredItem> = state
+    let items: Vec<StoredItem> = state
         .items
         .values()
+        .filter(|item| item.likes_count >= config.min_likes || curation.is_pinned(item))
         .cloned()
-        .filter(|item| item.likes_count >= config.min_likes)
         .collect();
+    // 手動の pin / hide / タイトル差し替えを反映する
+    let mut items = curation.apply(items);
 
-    // likes 降順 -> 公開日降順で並べる
-    items.sort_by(|a, b| compare_items(a, b));
-    if items.len() > config.max_feed_entries {
-        items.truncate(config.max_feed_entries);
+    // pin した item を先頭に固定する
+    match config.feed_sort {
+        // likes 降順 -> 公開日降順で並べる
+        FeedSort::Likes => items.sort_by(|a, b| compare_pinned_first(a, b, compare_items)),
+        FeedSort::Qualified => {
+            items.sort_by(|a, b| compare_pinned_first(a, b, compare_items_by_qualified))
+        }
     }
 
     let site_url = config.site_url.clone();
@@ -86,24 +463,88 @@ fn run() -> Result<(), AppError> {
         build_url(&site_url, "index.html")
     };
 
-    let entries = build_entries(&items, now);
-    let feed_updated = default_feed_updated(&entries, now);
-    let feed_id = if site_url.is_empty() {
-        format!("tag:qiita.com,{}:qiita-feed", now.format("%Y"))
-    } else {
-        feed_url.clone()
-    };
-    let feed = FeedInfo {
-        id: feed_id,
-        title: config.site_title.clone(),
-        description: config.site_description.clone(),
-        updated: feed_updated,
-        feed_url: feed_url.clone(),
-        index_url: index_url.clone(),
-        entries,
+    let source = EntrySource {
+        url: config.feed_source.clone(),
+        title: Some("Qiita".to_string()),
     };
+    let main_items = &items[..items.len().min(config.max_feed_entries)];
+    let feed = build_feed_info(
+        &config,
+        FeedSpec {
+            path: config.feed_path.clone(),
+            id_name: "qiita-feed".to_string(),
+            title: config.site_title.clone(),
+            description: config.site_description.clone(),
+        },
+        build_entries(main_items, config.entry_updated, &source, now),
+        &index_url,
+        now,
+    );
+    let feed_updated = feed.updated;
     let feed_xml = build_feed_xml(&feed)?;
 
+    // しきい値別・タグ別の feed は同じ state から選び直すだけで、追加の取得はしない
+    let mut extra_specs = Vec::new();
+    for tier in &config.tiers {
+        let selected = tier.select(&items, config.max_feed_entries);
+        let entries = build_entries(&selected, config.entry_updated, &source, now);
+        extra_specs.push(extra_feed_spec(&config, tier, entries));
+    }
+    for tag_feed in &config.tag_feeds {
+        let selected = tag_feed.select(&items, config.max_feed_entries);
+        let entries = build_entries(&selected, config.entry_updated, &source, now);
+        extra_specs.push(extra_feed_spec(&config, tag_feed, entries));
+    }
+    // 締まった期間ごとに上位の記事を 1 entry にまとめ、締めた時点の内容を state に残す
+    let digest_paths: HashSet<String> = config.digests.iter().map(|d| d.output_path()).collect();
+    state
+        .digests
+        .retain(|record| digest_paths.contains(&record.feed));
+    for digest in &config.digests {
+        digest.close_periods(&items, &mut state.digests, now);
+        let entries = digest.build_entries(&state.digests, &index_url, config.max_feed_entries);
+        extra_specs.push(extra_feed_spec(&config, digest, entries));
+    }
+    // 節目を超えた記事を 1 件ずつ出す (記録済みの節目から作るので二度は出ない)
+    if let Some(milestone_feed) = &config.milestone_feed {
+        let entries = milestone_feed.build_entries(&items, config.max_feed_entries);
+        extra_specs.push(extra_feed_spec(&config, milestone_feed, entries));
+    }
+    let mut extra_feeds = Vec::new();
+    for extra in extra_specs {
+        let path = site_output_path(&cli.out, &extra.spec.path);
+        let info = build_feed_info(&config, extra.spec, extra.entries, &index_url, now);
+        let xml = build_feed_xml(&info)?;
+        extra_feeds.push(ExtraFeedOutput {
+            kind: extra.kind,
+            name: extra.name,
+            path,
+            link: FeedLink {
+                title: info.title.clone(),
+                url: info.feed_url.clone(),
+            },
+            entries: info.entries.len(),
+            xml,
+        });
+    }
+
+    // RSS 2.0 しか読めないツール向けに同じ内容を出力する
+    let rss = match &config.rss_path {
+        Some(rss_path) => {
+            let rss_xml = build_rss_xml(&feed, &build_url(&site_url, rss_path))?;
+            Some((site_output_path(&cli.out, rss_path), rss_xml))
+        }
+        None => None,
+    };
+    // JavaScript から XML を解析せずに読めるよう JSON Feed も出力する
+    let json_feed = match &config.json_feed_path {
+        Some(json_feed_path) => {
+            let json = build_json_feed(&feed, &build_url(&site_url, json_feed_path))?;
+            Some((site_output_path(&cli.out, json_feed_path), json))
+        }
+        None => None,
+    };
+
     let index_page = IndexPage {
         title: config.site_title.clone(),
         description: config.site_description.clone(),
@@ -111,28 +552,229 @@ fn run() -> Result<(), AppError> {
         updated: feed_updated,
         min_likes: config.min_likes,
         feed_source: config.feed_source.clone(),
+        extra_feeds: extra_feeds.iter().map(|f| f.link.clone()).collect(),
     };
     let index_html = build_index_html(&index_page);
 
     if cli.dry_run {
+        for report in reports.iter().filter(|r| r.is_changed()) {
+            if report.is_new {
+                println!("new: {}", report.key);
+            }
+            for change in &report.changes {
+                println!(
+                    "changed: {} {}: {} -> {}",
+                    report.key,
+                    change.field,
+                    change.before.as_deref().unwrap_or("(none)"),
+                    change.after.as_deref().unwrap_or("(none)")
+                );
+            }
+        }
+        for removal in &prune_report.removed {
+            println!(
+                "prune: {} rule={} title={}",
+                removal.key, removal.rule, removal.title
+            );
+        }
+        for (rule, count) in prune_report.counts_by_rule() {
+            println!("prune-summary: rule={} removed={}", rule, count);
+        }
+        for extra in &extra_feeds {
+            println!(
+                "{}: name={} entries={} path={}",
+                extra.kind,
+                extra.name,
+                extra.entries,
+                extra.path.display()
+            );
+        }
         println!(
-            "dry-run: merged={} stored={} entries={}",
+            "dry-run: merged={} promoted={} refreshed={} stored={} watching={} entries={}",
             merged,
+            promoted,
+            refreshed,
             state.items.len(),
+            state.watchlist.len(),
             feed.entries.len()
         );
         return Ok(());
     }
 
-    write_output(&cli.out, &feed_xml)?;
-    write_output(&cli.index, &index_html)?;
-    write_output(&cli.last_build, &now.to_rfc3339())?;
+    write_output(&cli.out, &feed_xml, &config.precompress)?;
+    if let Some((rss_out, rss_xml)) = &rss {
+        write_output(rss_out, rss_xml, &config.precompress)?;
+    }
+    if let Some((json_out, json)) = &json_feed {
+        write_output(json_out, json, &config.precompress)?;
+    }
+    for extra in &extra_feeds {
+        write_output(&extra.path, &extra.xml, &config.precompress)?;
+    }
+    write_output(&cli.index, &index_html, &config.precompress)?;
+    write_output(&cli.last_build, &now.to_rfc3339(), &config.precompress)?;
     write_nojekyll(&cli.out)?;
-    state.save(&cli.state)?;
+    state.save_with_backups(&cli.state, config.state_backups)?;
 
     Ok(())
 }
 
+// feed ごとに異なる出力先とタイトル
+struct FeedSpec {
+    path: String,
+    /// site_url 未設定時の feed id に使う名前
+    id_name: String,
+    title: String,
+    description: String,
+}
+
+// feed.xml 以外の追加 feed (tiers / tag_feeds / digests / milestone_feed)
+struct ExtraFeedSpec {
+    kind: &'static str,
+    name: String,
+    spec: FeedSpec,
+    entries: Vec<FeedEntry>,
+}
+
+struct ExtraFeedOutput {
+    kind: &'static str,
+    name: String,
+    path: PathBuf,
+    link: FeedLink,
+    entries: usize,
+    xml: String,
+}
+
+fn extra_feed_spec(
+    config: &AppConfig,
+    feed: &dyn ExtraFeed,
+    entries: Vec<FeedEntry>,
+) -> ExtraFeedSpec {
+    ExtraFeedSpec {
+        kind: feed.kind(),
+        name: feed.name(),
+        spec: FeedSpec {
+            path: feed.output_path(),
+            id_name: feed.id_name(),
+            title: feed.feed_title(&config.site_title),
+            description: feed.feed_description(&config.site_description),
+        },
+        entries,
+    }
+}
+
+fn build_feed_info(
+    config: &AppConfig,
+    spec: FeedSpec,
+    entries: Vec<FeedEntry>,
+    index_url: &str,
+    now: DateTime<Utc>,
+) -> FeedInfo {
+    let site_url = &config.site_url;
+    let feed_url = build_url(site_url, &spec.path);
+    let feed_id = if site_url.is_empty() {
+        format!("tag:qiita.com,{}:{}", FIXED_ID_DATE, spec.id_name)
+    } else {
+        feed_url.clone()
+    };
+    FeedInfo {
+        id: feed_id,
+        title: spec.title,
+        description: spec.description,
+        updated: default_feed_updated(&entries, now),
+        feed_url,
+        index_url: index_url.to_string(),
+        lang: Some(config.site_lang.clone()),
+        icon: config
+            .site_icon
+            .as_deref()
+            .map(|icon| site_asset_url(site_url, icon)),
+        logo: config
+            .site_logo
+            .as_deref()
+            .map(|logo| site_asset_url(site_url, logo)),
+        rights: config.site_rights.clone(),
+        entries,
+    }
+}
+
+fn recheck_watchlist(
+    client: &QiitaClient,
+    state: &mut StateStore,
+    config: &AppConfig,
+    exclude: &HashSet<String>,
+    now: DateTime<Utc>,
+) {
+    let mut remaining = config.watchlist_budget;
+    for (key, item_id) in state.watch_candidates(exclude) {
+        if remaining == 0 {
+            break;
+        }
+        let max_pages = config.likes_max_pages.min(remaining);
+        // min_likes に達したかどうかだけ分かればよい
+        let likes = match client.fetch_likes(
+            &item_id,
+            config.likes_per_page,
+            max_pages,
+            Some(config.min_likes),
+        ) {
+            Ok(likes) => likes,
+            Err(err) if err.is_not_found() => {
+                // 削除された記事は見守っても feed 入りしない
+                state.watchlist.remove(&key);
+                remaining = remaining.saturating_sub(1);
+                continue;
+            }
+            Err(err) => {
+                eprintln!("watchlist 再確認失敗: item_id={} error={}", item_id, err);
+                break;
+            }
+        };
+        remaining = remaining.saturating_sub(likes.requests);
+        state.update_watch_likes(&key, likes, now);
+    }
+}
+
+fn refresh_stored_likes(
+    client: &QiitaClient,
+    state: &mut StateStore,
+    config: &AppConfig,
+    exclude: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> usize {
+    let mut remaining = config.likes_refresh_budget;
+    let mut refreshed = 0;
+    for (key, item_id) in state.refresh_candidates(exclude) {
+        if remaining == 0 {
+            break;
+        }
+        let max_pages = config.likes_max_pages.min(remaining);
+        // 再集計の失敗は本処理を止めずに次回へ回す
+        let mut likes = match client.fetch_likes(&item_id, config.likes_per_page, max_pages, None) {
+            Ok(likes) => likes,
+            Err(err) if err.is_not_found() => {
+                eprintln!("記事が削除されています: item_id={}", item_id);
+                state.mark_removed(&key, now);
+                remaining = remaining.saturating_sub(1);
+                continue;
+            }
+            Err(err) => {
+                eprintln!("likes 再集計失敗: item_id={} error={}", item_id, err);
+                break;
+            }
+        };
+        remaining = remaining.saturating_sub(likes.requests);
+        // 予算で絞ったページ数で打ち切った件数は頭打ちではないので、次の回で数え直す
+        if likes.lower_bound && max_pages < config.likes_max_pages {
+            likes.full_scan = false;
+        }
+        if state.update_likes(&key, likes, now) {
+            refreshed += 1;
+        }
+    }
+    refreshed
+}
+
 fn compare_items(a: &StoredItem, b: &StoredItem) -> Ordering {
     let likes = b.likes_count.cmp(&a.likes_count);
     if likes != Ordering::Equal {
@@ -144,6 +786,24 @@ fn compare_items(a: &StoredItem, b: &StoredItem) -> Ordering {
     b_published.cmp(&a_published)
 }
 
+fn compare_pinned_first(
+    a: &CuratedItem,
+    b: &CuratedItem,
+    compare: fn(&StoredItem, &StoredItem) -> Ordering,
+) -> Ordering {
+    b.pinned
+        .cmp(&a.pinned)
+        .then_with(|| compare(&a.item, &b.item))
+}
+
+fn compare_items_by_qualified(a: &StoredItem, b: &StoredItem) -> Ordering {
+    let qualified = b.qualified_time().cmp(&a.qualified_time());
+    if qualified != Ordering::Equal {
+        return qualified;
+    }
+    compare_items(a, b)
+}
+
 fn published_time(item: &StoredItem) -> Option<DateTime<FixedOffset>> {
     item.updated
         .as_deref()
@@ -155,36 +815,82 @@ fn published_time(item: &StoredItem) -> Option<DateTime<FixedOffset>> {
         })
 }
 
-fn build_entries(items: &[StoredItem], now: DateTime<Utc>) -> Vec<FeedEntry> {
+fn entry_updated_time(
+    item: &StoredItem,
+    entry_updated: EntryUpdated,
+) -> Option<DateTime<FixedOffset>> {
+    let published = || published_at(item);
+    match entry_updated {
+        EntryUpdated::Qiita => select_updated_time(item).or_else(|| published_time(item)),
+        EntryUpdated::Edited => item
+            .edited_at
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .or_else(published),
+        EntryUpdated::Qualified => DateTime::parse_from_rfc3339(&item.qualified_at)
+            .ok()
+            .or_else(published),
+    }
+}
+
+fn published_at(item: &StoredItem) -> Option<DateTime<FixedOffset>> {
+    item.published
+        .as_deref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+}
+
+fn build_entries(
+    items: &[CuratedItem],
+    entry_updated: EntryUpdated,
+    source: &EntrySource,
+    now: DateTime<Utc>,
+) -> Vec<FeedEntry> {
     items
         .iter()
-        .filter_map(|item| {
-            let updated = select_updated_time(item)
-                .or_else(|| published_time(item))
+        .map(|curated| {
+            let item = &curated.item;
+            let updated = entry_updated_time(item, entry_updated)
                 .unwrap_or_else(|| now.with_timezone(&FixedOffset::east_opt(0).unwrap()));
-            let id = build_entry_id(item, now);
+            let id = build_entry_id(item);
             let link = item.link.clone();
-            let summary_html = build_summary_html(item);
-            Some(FeedEntry {
+            let summary_html = build_summary_html(item, curated.note.as_deref());
+            FeedEntry {
                 id,
                 title: item.title.clone(),
                 link,
                 updated,
+                published: published_at(item),
+                author: item.author_name.clone().map(|name| FeedAuthor {
+                    name,
+                    uri: extract_username(&item.link)
+                        .map(|user| format!("https://qiita.com/{}", user)),
+                }),
                 summary_html,
-            })
+                source: Some(source.clone()),
+                qiita: Some(QiitaMeta {
+                    item_id: item.item_id.clone(),
+                    likes: item.likes_count,
+                    likes_lower_bound: item.likes_lower_bound,
+                    stocks: item.stocks_count,
+                    tags: item.tags.clone(),
+                }),
+            }
         })
         .collect()
 }
 
-fn build_entry_id(item: &StoredItem, now: DateTime<Utc>) -> String {
-    if let Some(id) = &item.item_id {
-        return format!("tag:qiita.com,{}:{}", now.format("%Y"), id);
-    }
-    format!("tag:qiita.com,{}:unknown", now.format("%Y"))
+fn build_entry_id(item: &StoredItem) -> String {
+    item.entry_id
+        .clone()
+        .unwrap_or_else(|| stable_entry_id(item))
 }
 
-fn build_summary_html(item: &StoredItem) -> String {
-    let likes = format!("Likes: {}", item.likes_count);
+fn build_summary_html(item: &StoredItem, note: Option<&str>) -> String {
+    let likes = if item.likes_lower_bound {
+        format!("Likes: ≥ {}", item.likes_count)
+    } else {
+        format!("Likes: {}", item.likes_count)
+    };
     let author = match (&item.author_name, extract_username(&item.link)) {
         (Some(name), Some(username)) => format!(
             "Author: <a href=\"https://qiita.com/{username}\">{name}</a>",
@@ -198,22 +904,16 @@ fn build_summary_html(item: &StoredItem) -> String {
     let updated = item.updated.as_deref().unwrap_or("unknown");
     let content = item.summary.as_deref().unwrap_or("(no content)");
 
+    let note = note
+        .map(|n| format!("Editor's note: {}<br/>", escape_html(n)))
+        .unwrap_or_default();
+
     format!(
-        "{}<br/>{}<br/>Published: {}<br/>Updated: {}<br/>{}",
-        likes, author, published, updated, content
+        "{}{}<br/>{}<br/>Published: {}<br/>Updated: {}<br/>{}",
+        note, likes, author, published, updated, content
     )
 }
 
-fn extract_username(link: &str) -> Option<String> {
-    let trimmed = link.split('?').next().unwrap_or(link).trim_end_matches('/');
-    let parts: Vec<&str> = trimmed.split('/').collect();
-    let items_index = parts.iter().position(|p| *p == "items")?;
-    if items_index == 0 {
-        return None;
-    }
-    Some(parts[items_index - 1].to_string())
-}
-
 fn build_url(base: &str, path: &str) -> String {
     if base.is_empty() {
         return path.to_string();
@@ -221,17 +921,31 @@ fn build_url(base: &str, path: &str) -> String {
     format!("{}/{}", base.trim_end_matches('/'), path.trim_start_matches('/'))
 }
 
-fn write_output(path: &PathBuf, content: &str) -> Result<(), AppError> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| AppError::feed(format!("出力ディレクトリ作成失敗: {}", e)))?;
+/// icon / logo の指定を URL にする。絶対 URL はそのまま使う
+fn site_asset_url(site_url: &str, value: &str) -> String {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        value.to_string()
+    } else {
+        build_url(site_url, value)
     }
-    fs::write(path, content)
+}
+
+/// サイト内のパス (feed_path などと同じ形式) を --out と同じディレクトリ基準のファイルパスにする
+fn site_output_path(out: &Path, site_path: &str) -> PathBuf {
+    let dir = out.parent().unwrap_or_else(|| Path::new(""));
+    dir.join(site_path.trim_start_matches('/'))
+}
+
+fn write_output(path: &Path, content: &str, precompress: &[Precompress]) -> Result<(), AppError> {
+    write_atomic(path, content.as_bytes())
         .map_err(|e| AppError::feed(format!("出力書き込み失敗: {}", e)))?;
+    // 配信側で圧縮しなくて済むよう圧縮済みのファイルも置く
+    write_precompressed(path, content.as_bytes(), precompress)
+        .map_err(|e| AppError::feed(format!("圧縮済み出力の書き込み失敗: {}", e)))?;
     Ok(())
 }
 
-fn write_nojekyll(out_path: &PathBuf) -> Result<(), AppError> {
+fn write_nojekyll(out_path: &Path) -> Result<(), AppError> {
     let Some(parent) = out_path.parent() else {
         return Ok(());
     };
@@ -239,7 +953,7 @@ fn write_nojekyll(out_path: &PathBuf) -> Result<(), AppError> {
     if nojekyll_path.exists() {
         return Ok(());
     }
-    fs::write(&nojekyll_path, "")
+    write_atomic(&nojekyll_path, b"")
         .map_err(|e| AppError::feed(format!(".nojekyll 作成失敗: {}", e)))?;
     Ok(())
 }
@@ -10,7 +10,34 @@ pub struct FeedEntry {
     pub title: String,
     pub link: String,
     pub updated: DateTime<FixedOffset>,
+    pub published: Option<DateTime<FixedOffset>>,
+    pub author: Option<FeedAuthor>,
     pub summary_html: String,
+    /// 転載元の feed
+    pub source: Option<EntrySource>,
+    /// 元になった Qiita 記事の情報 (JSON Feed の _qiita 拡張で使う)
+    pub qiita: Option<QiitaMeta>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedAuthor {
+    pub name: String,
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EntrySource {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QiitaMeta {
+    pub item_id: Option<String>,
+    pub likes: u32,
+    pub likes_lower_bound: bool,
+    pub stocks: Option<u32>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -21,9 +48,17 @@ pub struct FeedInfo {
     pub updated: DateTime<FixedOffset>,
     pub feed_url: String,
     pub index_url: String,
+    /// xml:lang (ja など)
+    pub lang: Option<String>,
+    pub icon: Option<String>,
+    pub logo: Option<String>,
+    pub rights: Option<String>,
     pub entries: Vec<FeedEntry>,
 }
 
+/// feed-level の generator に出す名前
+pub const GENERATOR_NAME: &str = "qiita-feed";
+
 pub fn build_feed_xml(feed: &FeedInfo) -> Result<String, AppError> {
     let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
     writer
@@ -32,6 +67,9 @@ pub fn build_feed_xml(feed: &FeedInfo) -> Result<String, AppError> {
 
     let mut feed_start = BytesStart::new("feed");
     feed_start.push_attribute(("xmlns", "http://www.w3.org/2005/Atom"));
+    if let Some(lang) = non_empty(feed.lang.as_deref()) {
+        feed_start.push_attribute(("xml:lang", lang));
+    }
     writer
         .write_event(Event::Start(feed_start))
         .map_err(|e| AppError::feed(format!("feed 開始失敗: {}", e)))?;
@@ -47,6 +85,37 @@ pub fn build_feed_xml(feed: &FeedInfo) -> Result<String, AppError> {
         write_text_element(&mut writer, "subtitle", &feed.description)?;
     }
 
+    // author の無い entry があっても妥当な Atom になるよう feed 全体の author を置く
+    write_author(
+        &mut writer,
+        &FeedAuthor {
+            name: feed.title.clone(),
+            uri: Some(feed.index_url.clone()),
+        },
+    )?;
+
+    let mut generator = BytesStart::new("generator");
+    generator.push_attribute(("version", env!("CARGO_PKG_VERSION")));
+    writer
+        .write_event(Event::Start(generator))
+        .map_err(|e| AppError::feed(format!("generator 開始失敗: {}", e)))?;
+    writer
+        .write_event(Event::Text(BytesText::new(GENERATOR_NAME)))
+        .map_err(|e| AppError::feed(format!("generator 書き込み失敗: {}", e)))?;
+    writer
+        .write_event(Event::End(BytesEnd::new("generator")))
+        .map_err(|e| AppError::feed(format!("generator 終了失敗: {}", e)))?;
+
+    if let Some(icon) = non_empty(feed.icon.as_deref()) {
+        write_text_element(&mut writer, "icon", icon)?;
+    }
+    if let Some(logo) = non_empty(feed.logo.as_deref()) {
+        write_text_element(&mut writer, "logo", logo)?;
+    }
+    if let Some(rights) = non_empty(feed.rights.as_deref()) {
+        write_text_element(&mut writer, "rights", rights)?;
+    }
+
     for entry in &feed.entries {
         writer
             .write_event(Event::Start(BytesStart::new("entry")))
@@ -56,6 +125,15 @@ pub fn build_feed_xml(feed: &FeedInfo) -> Result<String, AppError> {
         write_text_element(&mut writer, "title", &entry.title)?;
         write_link(&mut writer, "alternate", &entry.link)?;
         write_text_element(&mut writer, "updated", &entry.updated.to_rfc3339())?;
+        if let Some(published) = entry.published {
+            write_text_element(&mut writer, "published", &published.to_rfc3339())?;
+        }
+        if let Some(author) = entry.author.as_ref().filter(|a| !a.name.trim().is_empty()) {
+            write_author(&mut writer, author)?;
+        }
+        if let Some(source) = &entry.source {
+            write_source(&mut writer, source)?;
+        }
 
         let mut summary = BytesStart::new("summary");
         summary.push_attribute(("type", "html"));
@@ -93,7 +171,7 @@ pub fn default_feed_updated(entries: &[FeedEntry], now: DateTime<Utc>) -> DateTi
         .unwrap_or_else(|| now.with_timezone(&FixedOffset::east_opt(0).unwrap()))
 }
 
-fn write_text_element(
+pub(crate) fn write_text_element(
     writer: &mut Writer<Cursor<Vec<u8>>>,
     name: &str,
     value: &str,
@@ -110,6 +188,42 @@ fn write_text_element(
     Ok(())
 }
 
+fn write_author(writer: &mut Writer<Cursor<Vec<u8>>>, author: &FeedAuthor) -> Result<(), AppError> {
+    writer
+        .write_event(Event::Start(BytesStart::new("author")))
+        .map_err(|e| AppError::feed(format!("author 開始失敗: {}", e)))?;
+    write_text_element(writer, "name", &author.name)?;
+    if let Some(uri) = non_empty(author.uri.as_deref()) {
+        write_text_element(writer, "uri", uri)?;
+    }
+    writer
+        .write_event(Event::End(BytesEnd::new("author")))
+        .map_err(|e| AppError::feed(format!("author 終了失敗: {}", e)))?;
+    Ok(())
+}
+
+fn write_source(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    source: &EntrySource,
+) -> Result<(), AppError> {
+    writer
+        .write_event(Event::Start(BytesStart::new("source")))
+        .map_err(|e| AppError::feed(format!("source 開始失敗: {}", e)))?;
+    write_text_element(writer, "id", &source.url)?;
+    if let Some(title) = non_empty(source.title.as_deref()) {
+        write_text_element(writer, "title", title)?;
+    }
+    write_link(writer, "self", &source.url)?;
+    writer
+        .write_event(Event::End(BytesEnd::new("source")))
+        .map_err(|e| AppError::feed(format!("source 終了失敗: {}", e)))?;
+    Ok(())
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
 fn write_link(
     writer: &mut Writer<Cursor<Vec<u8>>>,
     rel: &str,
@@ -0,0 +1,61 @@
+/// 記事 URL を比較用の正規形にする。クエリ・フラグメント・末尾の / を落とし、scheme と host を
+/// 小文字にそろえる (qiita.com は https に統一)
+pub fn canonicalize_url(link: &str) -> String {
+    let trimmed = link.trim();
+    let end = trimmed.find(['?', '#']).unwrap_or(trimmed.len());
+    let without_query = &trimmed[..end];
+    let Some((scheme, rest)) = without_query.split_once("://") else {
+        return without_query.trim_end_matches('/').to_string();
+    };
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    let mut host = host.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "https" => Some(":443"),
+        "http" => Some(":80"),
+        _ => None,
+    };
+    if let Some(port) = default_port {
+        if let Some(stripped) = host.strip_suffix(port) {
+            host = stripped.to_string();
+        }
+    }
+    let scheme = if host == "qiita.com" {
+        "https"
+    } else {
+        scheme.as_str()
+    };
+    format!("{}://{}{}", scheme, host, path.trim_end_matches('/'))
+}
+
+/// 記事 URL (/<user>/items/<id>) から item_id を取り出す
+pub fn extract_item_id(link: &str) -> Option<String> {
+    let segments = path_segments(link);
+    let index = segments.iter().position(|s| s == "items")?;
+    segments.get(index + 1).filter(|s| !s.is_empty()).cloned()
+}
+
+/// 記事 URL (/<user>/items/<id>) からユーザー名を取り出す
+pub fn extract_username(link: &str) -> Option<String> {
+    let segments = path_segments(link);
+    let index = segments.iter().position(|s| s == "items")?;
+    if index == 0 {
+        return None;
+    }
+    Some(segments[index - 1].clone())
+}
+
+fn path_segments(link: &str) -> Vec<String> {
+    let canonical = canonicalize_url(link);
+    let path = match canonical.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |index| &rest[index..]),
+        None => canonical.as_str(),
+    };
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,121 @@
+use crate::fsio::write_atomic;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// gzip ストリームの先頭 2 バイト
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+const BROTLI_QUALITY: u32 = 11;
+const BROTLI_WINDOW: u32 = 22;
+
+// 公開する成果物と一緒に書き出す圧縮済みファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precompress {
+    /// feed.xml.gz
+    Gzip,
+    /// feed.xml.br
+    Brotli,
+}
+
+impl Precompress {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Precompress::Gzip => "gz",
+            Precompress::Brotli => "br",
+        }
+    }
+
+    pub fn compress(self, content: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Precompress::Gzip => gzip(content),
+            Precompress::Brotli => brotli(content),
+        }
+    }
+}
+
+pub fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// articles.json.gz -> articles.json。.gz でなければそのまま
+pub fn strip_gzip_extension(path: &Path) -> PathBuf {
+    if is_gzip_path(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// feed.xml -> feed.xml.gz
+pub fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+pub fn gzip(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(content)?;
+    encoder.finish()
+}
+
+pub fn brotli(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut writer =
+            brotli::CompressorWriter::new(&mut out, 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+        writer.write_all(content)?;
+        writer.flush()?;
+    }
+    Ok(out)
+}
+
+/// ファイルを読み、先頭が gzip のマジックバイトなら展開して返す
+pub fn read_maybe_gzip(path: &Path) -> io::Result<Vec<u8>> {
+    let content = fs::read(path)?;
+    if !content.starts_with(&GZIP_MAGIC) {
+        return Ok(content);
+    }
+    let mut decoded = Vec::new();
+    GzDecoder::new(content.as_slice()).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+/// UTF-8 のテキストとして読む。gzip なら展開する
+pub fn read_to_string_maybe_gzip(path: &Path) -> io::Result<String> {
+    String::from_utf8(read_maybe_gzip(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// 拡張子が .gz か、既存のファイルが gzip なら圧縮して書き込む
+pub fn write_atomic_maybe_gzip(path: &Path, content: &[u8]) -> io::Result<()> {
+    if is_gzip_path(path) || file_is_gzip(path) {
+        write_atomic(path, &gzip(content)?)
+    } else {
+        write_atomic(path, content)
+    }
+}
+
+/// formats ごとに圧縮したファイルを path の隣に書き出す (feed.xml -> feed.xml.gz)
+pub fn write_precompressed(path: &Path, content: &[u8], formats: &[Precompress]) -> io::Result<()> {
+    for format in formats {
+        let compressed = format.compress(content)?;
+        write_atomic(&with_added_extension(path, format.extension()), &compressed)?;
+    }
+    Ok(())
+}
+
+fn file_is_gzip(path: &Path) -> bool {
+    let mut magic = [0u8; 2];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| magic == GZIP_MAGIC)
+}
@@ -1,5 +1,16 @@
+use crate::compress::Precompress;
+use crate::digest::{validate_digests, DigestFeed};
+use crate::entry_id::EntryIdMode;
 use crate::error::AppError;
+use crate::extra_feed::ExtraFeed;
+use crate::merge::MergePolicy;
+use crate::milestone::{validate_milestone_feed, MilestoneFeed};
+use crate::prune::{default_prune_rules, PruneRule};
+use crate::state::DEFAULT_STATE_BACKUPS;
+use crate::tag_feed::{validate_tag_feeds, TagFeed};
+use crate::tier::{validate_tiers, FeedTier};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::path::Path;
@@ -16,10 +27,126 @@ pub struct AppConfig {
     pub site_title: String,
     pub site_description: String,
     pub site_url: String,
+    /// feed の言語 (Atom の xml:lang など)
+    #[serde(default = "default_site_lang")]
+    pub site_lang: String,
+    /// feed の icon / logo 画像 (サイト内のパスか絶対 URL)。未指定なら出力しない
+    #[serde(default)]
+    pub site_icon: Option<String>,
+    #[serde(default)]
+    pub site_logo: Option<String>,
+    /// feed の rights (著作権表記)。未指定なら出力しない
+    #[serde(default)]
+    pub site_rights: Option<String>,
     pub feed_path: String,
+    /// RSS 2.0 版の出力先 (feed_path と同じくサイト内のパス)。未指定なら出力しない
+    #[serde(default)]
+    pub rss_path: Option<String>,
+    /// JSON Feed 1.1 版の出力先 (サイト内のパス)。未指定なら出力しない
+    #[serde(default)]
+    pub json_feed_path: Option<String>,
+    /// min_likes を変えた追加 feed
+    #[serde(default)]
+    pub tiers: Vec<FeedTier>,
+    /// 期間ごとの上位記事をまとめた feed
+    #[serde(default)]
+    pub digests: Vec<DigestFeed>,
+    /// likes の節目を超えた記事の feed。未指定なら節目を検出しない
+    #[serde(default)]
+    pub milestone_feed: Option<MilestoneFeed>,
+    /// タグごとの追加 feed
+    #[serde(default)]
+    pub tag_feeds: Vec<TagFeed>,
     pub feed_source: String,
     #[serde(default)]
     pub qiita_api_token: Option<String>,
+    #[serde(default)]
+    pub likes_count_mode: LikesCountMode,
+    #[serde(default = "default_likes_exact_recount_hours")]
+    pub likes_exact_recount_hours: u32,
+    /// feed から外れた保存済み item の likes 再集計に使う 1 回あたりのリクエスト上限 (0 で無効)
+    #[serde(default)]
+    pub likes_refresh_budget: u32,
+    /// min_likes 未満の候補を watchlist で再確認し続ける日数 (0 で無効)
+    #[serde(default)]
+    pub watchlist_days: u32,
+    /// watchlist の再確認に使う 1 回あたりのリクエスト上限
+    #[serde(default = "default_watchlist_budget")]
+    pub watchlist_budget: u32,
+    /// feed 入りした item の stocks 数も取得して推移に記録する
+    #[serde(default)]
+    pub fetch_stocks: bool,
+    /// state 保存時に残すバックアップの世代数
+    #[serde(default = "default_state_backups")]
+    pub state_backups: usize,
+    #[serde(default)]
+    pub feed_sort: FeedSort,
+    #[serde(default)]
+    pub entry_updated: EntryUpdated,
+    #[serde(default)]
+    pub entry_id: EntryIdMode,
+    #[serde(default)]
+    pub merge_policy: MergePolicy,
+    /// 保存済み item の削除ルール。未指定なら pinned と max_stored_days による削除
+    #[serde(default)]
+    pub prune_rules: Option<Vec<PruneRule>>,
+    /// 手動の pin / hide / タイトル差し替え (config からの相対パス)。未指定なら同じディレクトリの curation.yaml
+    #[serde(default)]
+    pub curation_path: Option<String>,
+    /// 出力ファイルと一緒に書き出す圧縮済みファイル (gzip / brotli)
+    #[serde(default)]
+    pub precompress: Vec<Precompress>,
+}
+
+// feed のエントリ順
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedSort {
+    /// likes 降順 -> 公開日降順
+    #[default]
+    Likes,
+    /// feed 入りした時刻の新しい順 (同時刻なら likes 降順)
+    Qualified,
+}
+
+// Atom の entry <updated> に使う時刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryUpdated {
+    /// Qiita の updated (無ければ published)。軽微な編集でも変わる
+    #[default]
+    Qiita,
+    /// タイトル・summary の変化を検出した時刻 (無ければ published)
+    Edited,
+    /// feed 入りした時刻
+    Qualified,
+}
+
+// likes の集計方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LikesCountMode {
+    /// 毎回すべてのページを辿って正確に数える
+    #[default]
+    Exact,
+    /// min_likes に達した時点で打ち切り、正確な再集計は likes_exact_recount_hours ごとに行う
+    Threshold,
+}
+
+fn default_site_lang() -> String {
+    "ja".to_string()
+}
+
+fn default_likes_exact_recount_hours() -> u32 {
+    24
+}
+
+fn default_watchlist_budget() -> u32 {
+    30
+}
+
+fn default_state_backups() -> usize {
+    DEFAULT_STATE_BACKUPS
 }
 
 impl AppConfig {
@@ -71,12 +198,63 @@ impl AppConfig {
                 "max_stored_items は 1 以上で指定してください",
             ));
         }
+        if self.likes_exact_recount_hours == 0 {
+            return Err(AppError::config(
+                "likes_exact_recount_hours は 1 以上で指定してください",
+            ));
+        }
+        self.merge_policy.validate()?;
+        for rule in self.prune_rules.iter().flatten() {
+            rule.validate()?;
+        }
+        validate_tiers(&self.tiers, self.min_likes)?;
+        validate_tag_feeds(&self.tag_feeds)?;
+        validate_digests(&self.digests)?;
+        if let Some(feed) = &self.milestone_feed {
+            validate_milestone_feed(feed)?;
+        }
+        self.validate_output_paths()?;
         if self.feed_source.trim().is_empty() {
             return Err(AppError::config("feed_source が空です"));
         }
         Ok(())
     }
 
+    // 出力先が重なると後から書いた feed で上書きされる
+    fn validate_output_paths(&self) -> Result<(), AppError> {
+        let mut paths = vec![self.feed_path.clone()];
+        paths.extend(self.rss_path.clone());
+        paths.extend(self.json_feed_path.clone());
+        paths.extend(self.extra_feeds().iter().map(|feed| feed.output_path()));
+        let mut seen = HashSet::new();
+        for path in &paths {
+            if !seen.insert(path.trim_start_matches('/')) {
+                return Err(AppError::config(format!(
+                    "出力先が重複しています: {}",
+                    path
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// feed.xml と同じ state から作る追加の feed (tiers / tag_feeds / digests / milestone_feed の順)
+    pub fn extra_feeds(&self) -> Vec<&dyn ExtraFeed> {
+        let mut feeds: Vec<&dyn ExtraFeed> = Vec::new();
+        feeds.extend(self.tiers.iter().map(|f| f as &dyn ExtraFeed));
+        feeds.extend(self.tag_feeds.iter().map(|f| f as &dyn ExtraFeed));
+        feeds.extend(self.digests.iter().map(|f| f as &dyn ExtraFeed));
+        feeds.extend(self.milestone_feed.iter().map(|f| f as &dyn ExtraFeed));
+        feeds
+    }
+
+    /// 実際に使う prune ルール
+    pub fn effective_prune_rules(&self) -> Vec<PruneRule> {
+        self.prune_rules
+            .clone()
+            .unwrap_or_else(|| default_prune_rules(self.max_stored_days))
+    }
+
     fn ensure_site_url(&mut self) {
         if !self.site_url.trim().is_empty() {
             self.site_url = normalize_site_url(&self.site_url);
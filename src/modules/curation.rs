@@ -0,0 +1,170 @@
+use crate::canonical::canonicalize_url;
+use crate::error::AppError;
+use crate::state::StoredItem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// curation_path 未指定時に config と同じディレクトリから探すファイル名
+pub const DEFAULT_CURATION_FILE: &str = "curation.yaml";
+
+// 手動で管理する記事ごとの上書き設定 (curation.yaml)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Curation {
+    #[serde(default)]
+    pub items: Vec<CurationEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurationEntry {
+    /// 対象の item (key / item_id / link のいずれか)
+    pub item_id: String,
+    /// feed の先頭に固定する (min_likes 未満でも載せる)
+    #[serde(default)]
+    pub pin: bool,
+    /// feed に載せない (state には残す)
+    #[serde(default)]
+    pub hide: bool,
+    /// feed 上のタイトルを差し替える
+    #[serde(default)]
+    pub title: Option<String>,
+    /// summary の先頭に付ける編集者コメント
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+// curation を反映した feed 用の item
+#[derive(Debug, Clone)]
+pub struct CuratedItem {
+    pub item: StoredItem,
+    pub pinned: bool,
+    pub note: Option<String>,
+}
+
+impl CuratedItem {
+    /// curation の無い item をそのまま包む
+    pub fn plain(item: StoredItem) -> Self {
+        Self {
+            item,
+            pinned: false,
+            note: None,
+        }
+    }
+}
+
+impl Curation {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            AppError::config(format!(
+                "curation 読み込み失敗: path={} error={}",
+                path.display(),
+                e
+            ))
+        })?;
+        let curation: Curation = serde_yaml::from_str(&content)
+            .map_err(|e| AppError::config(format!("curation パース失敗: {}", e)))?;
+        curation.validate()?;
+        Ok(curation)
+    }
+
+    /// config の curation_path を読む。未指定なら config と同じディレクトリの curation.yaml を
+    /// あれば読み、無ければ空とする
+    pub fn load_for_config(
+        config_path: &Path,
+        curation_path: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let base = config_path.parent().unwrap_or_else(|| Path::new(""));
+        match curation_path {
+            Some(path) => Self::load(&resolve_path(base, path)),
+            None => {
+                let path = base.join(DEFAULT_CURATION_FILE);
+                if path.exists() {
+                    Self::load(&path)
+                } else {
+                    Ok(Self::default())
+                }
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        let mut seen = HashSet::new();
+        for entry in &self.items {
+            let id = entry.item_id.trim();
+            if id.is_empty() {
+                return Err(AppError::config("curation の item_id が空です"));
+            }
+            if !seen.insert(id) {
+                return Err(AppError::config(format!(
+                    "curation の item_id が重複しています: {}",
+                    id
+                )));
+            }
+            if entry.pin && entry.hide {
+                return Err(AppError::config(format!(
+                    "curation の pin と hide は同時に指定できません: {}",
+                    id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, item: &StoredItem) -> Option<&CurationEntry> {
+        self.items.iter().find(|entry| {
+            let id = entry.item_id.trim();
+            id == item.key
+                || item.item_id.as_deref() == Some(id)
+                || canonicalize_url(id) == canonicalize_url(&item.link)
+        })
+    }
+
+    pub fn is_pinned(&self, item: &StoredItem) -> bool {
+        self.find(item).is_some_and(|entry| entry.pin)
+    }
+
+    /// pin されている item の key。prune で state の pinned と同じく扱う
+    pub fn pinned_keys<'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a StoredItem>,
+    ) -> HashSet<String> {
+        items
+            .into_iter()
+            .filter(|item| self.is_pinned(item))
+            .map(|item| item.key.clone())
+            .collect()
+    }
+
+    /// hide の item を除き、タイトルの差し替えと pin / note を反映する。並び順は変えない
+    pub fn apply(&self, items: Vec<StoredItem>) -> Vec<CuratedItem> {
+        items
+            .into_iter()
+            .filter_map(|mut item| {
+                let Some(entry) = self.find(&item) else {
+                    return Some(CuratedItem::plain(item));
+                };
+                if entry.hide {
+                    return None;
+                }
+                if let Some(title) = entry.title.as_ref().filter(|t| !t.trim().is_empty()) {
+                    item.title = title.clone();
+                }
+                Some(CuratedItem {
+                    item,
+                    pinned: entry.pin,
+                    note: entry.note.clone().filter(|n| !n.trim().is_empty()),
+                })
+            })
+            .collect()
+    }
+}
+
+fn resolve_path(base: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
@@ -0,0 +1,281 @@
+use crate::atom::FeedEntry;
+use crate::curation::CuratedItem;
+use crate::error::AppError;
+use crate::entry_id::FIXED_ID_DATE;
+use crate::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use crate::html::escape_html;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+// 期間ごとに上位の記事をまとめた feed の 1 entry の単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    /// 0 時から 24 時間
+    Daily,
+    /// 月曜 0 時から 7 日間 (ISO 週)
+    Weekly,
+}
+
+impl DigestPeriod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DigestPeriod::Daily => "daily",
+            DigestPeriod::Weekly => "weekly",
+        }
+    }
+
+    fn length(self) -> Duration {
+        match self {
+            DigestPeriod::Daily => Duration::days(1),
+            DigestPeriod::Weekly => Duration::weeks(1),
+        }
+    }
+
+    /// at を含む期間の開始時刻
+    fn start_of(self, at: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        let date = match self {
+            DigestPeriod::Daily => at.date_naive(),
+            DigestPeriod::Weekly => {
+                at.date_naive() - Duration::days(at.weekday().num_days_from_monday() as i64)
+            }
+        };
+        at.offset()
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .single()
+            .unwrap_or(at)
+    }
+
+    /// entry の id に使う期間の名前 (2026-10-17 / 2026-W42)
+    fn label(self, start: DateTime<FixedOffset>) -> String {
+        match self {
+            DigestPeriod::Daily => start.format("%Y-%m-%d").to_string(),
+            DigestPeriod::Weekly => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestFeed {
+    pub period: DigestPeriod,
+    /// 1 entry に載せる記事数
+    #[serde(default = "default_digest_top")]
+    pub top: usize,
+    /// feed に残す期間の数
+    #[serde(default = "default_digest_periods")]
+    pub periods: usize,
+    /// 期間の区切りに使う UTC からの時差 (時間)
+    #[serde(default = "default_digest_utc_offset_hours")]
+    pub utc_offset_hours: i32,
+    /// path 未指定なら feeds/digest-<period>.xml、title 未指定なら「<site_title> (daily digest)」
+    #[serde(flatten)]
+    pub options: ExtraFeedOptions,
+}
+
+fn default_digest_top() -> usize {
+    10
+}
+
+fn default_digest_periods() -> usize {
+    7
+}
+
+fn default_digest_utc_offset_hours() -> i32 {
+    9
+}
+
+impl ExtraFeed for DigestFeed {
+    fn kind(&self) -> &'static str {
+        "digest-feed"
+    }
+
+    fn name(&self) -> String {
+        self.period.as_str().to_string()
+    }
+
+    fn id_name(&self) -> String {
+        format!(
+            "qiita-feed:digests:{}",
+            self.output_path().trim_start_matches('/')
+        )
+    }
+
+    fn options(&self) -> &ExtraFeedOptions {
+        &self.options
+    }
+
+    fn default_path(&self) -> String {
+        format!("feeds/digest-{}.xml", self.period.as_str())
+    }
+
+    fn default_title(&self, site_title: &str) -> String {
+        format!("{} ({} digest)", site_title, self.period.as_str())
+    }
+}
+
+impl DigestFeed {
+    fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset_hours * 3600)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap())
+    }
+
+    /// 締まった期間のうち未記録のものについて、その期間に feed 入りした item の likes 上位を
+    /// records に記録する (item の無い期間は記録しない)。以後の likes 変化や prune・curation では
+    /// 作り直さない。periods より古いこの feed の記録は消す。新しく記録した数を返す
+    pub fn close_periods(
+        &self,
+        items: &[CuratedItem],
+        records: &mut Vec<DigestRecord>,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let feed = self.output_path();
+        let length = self.period.length();
+        let current = self.period.start_of(now.with_timezone(&self.offset()));
+        let oldest = current - length * self.periods as i32;
+        records.retain(|r| r.feed != feed || r.start_time().is_some_and(|t| t >= oldest));
+        let mut closed = 0;
+        for back in 1..=self.periods as i32 {
+            let start = current - length * back;
+            let end = start + length;
+            let label = self.period.label(start);
+            if records.iter().any(|r| r.feed == feed && r.label == label) {
+                continue;
+            }
+            let mut selected: Vec<&CuratedItem> = items
+                .iter()
+                .filter(|item| {
+                    item.item
+                        .qualified_time()
+                        .is_some_and(|t| t >= start && t < end)
+                })
+                .collect();
+            if selected.is_empty() {
+                continue;
+            }
+            selected.sort_by(|a, b| {
+                b.item
+                    .likes_count
+                    .cmp(&a.item.likes_count)
+                    .then_with(|| a.item.key.cmp(&b.item.key))
+            });
+            selected.truncate(self.top);
+
+            records.push(DigestRecord {
+                feed: feed.clone(),
+                title: format!("digest {} (top {})", label, selected.len()),
+                label,
+                summary_html: build_digest_html(&selected),
+                start: start.to_rfc3339(),
+                end: end.to_rfc3339(),
+            });
+            closed += 1;
+        }
+        closed
+    }
+
+    /// 記録済みの期間ごとに entry を作り、新しい期間から順に最大 max_entries 件並べる
+    pub fn build_entries(
+        &self,
+        records: &[DigestRecord],
+        link: &str,
+        default_max: usize,
+    ) -> Vec<FeedEntry> {
+        let feed = self.output_path();
+        let mut records: Vec<(&DigestRecord, DateTime<FixedOffset>)> = records
+            .iter()
+            .filter(|r| r.feed == feed)
+            .filter_map(|r| {
+                DateTime::parse_from_rfc3339(&r.end)
+                    .ok()
+                    .map(|end| (r, end))
+            })
+            .collect();
+        records.sort_by_key(|(_, end)| Reverse(*end));
+        records
+            .into_iter()
+            .take(self.options.max_entries(default_max))
+            .map(|(record, end)| FeedEntry {
+                // 期間の年を入れると ISO 週の年と食い違うことがあるため固定の日付を使う
+                id: format!("tag:qiita.com,{}:digest-{}", FIXED_ID_DATE, record.label),
+                title: record.title.clone(),
+                link: link.to_string(),
+                updated: end,
+                published: Some(end),
+                author: None,
+                summary_html: record.summary_html.clone(),
+                source: None,
+                qiita: None,
+            })
+            .collect()
+    }
+}
+
+// 締まった期間の digest。締めた時点の内容で固定して state に残す
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestRecord {
+    /// digest feed の出力先 (同じ period の feed を区別する)
+    pub feed: String,
+    /// 期間の名前 (2026-10-17 / 2026-W42)
+    pub label: String,
+    pub title: String,
+    pub summary_html: String,
+    pub start: String,
+    pub end: String,
+}
+
+impl DigestRecord {
+    fn start_time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.start).ok()
+    }
+}
+
+fn build_digest_html(items: &[&CuratedItem]) -> String {
+    let list: String = items
+        .iter()
+        .map(|curated| {
+            let item = &curated.item;
+            let likes = if item.likes_lower_bound {
+                format!("≥ {}", item.likes_count)
+            } else {
+                item.likes_count.to_string()
+            };
+            format!(
+                "<li><a href=\"{}\">{}</a> (Likes: {})</li>",
+                escape_html(&item.link),
+                escape_html(&item.title),
+                likes
+            )
+        })
+        .collect();
+    format!("<ol>{}</ol>", list)
+}
+
+pub fn validate_digests(digests: &[DigestFeed]) -> Result<(), AppError> {
+    for digest in digests {
+        let period = digest.period.as_str();
+        if digest.top == 0 {
+            return Err(AppError::config(format!(
+                "digests の top は 1 以上で指定してください: {}",
+                period
+            )));
+        }
+        if digest.periods == 0 {
+            return Err(AppError::config(format!(
+                "digests の periods は 1 以上で指定してください: {}",
+                period
+            )));
+        }
+        if !(-23..=23).contains(&digest.utc_offset_hours) {
+            return Err(AppError::config(format!(
+                "digests の utc_offset_hours は -23 から 23 で指定してください: {}",
+                period
+            )));
+        }
+        digest.options.validate("digests", period)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use crate::canonical::canonicalize_url;
+use crate::fingerprint::link_hash;
+use crate::state::StoredItem;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 記事ごとでない id (feed や digest の entry) に入れる日付。id が変わらないよう固定する
+pub const FIXED_ID_DATE: &str = "2026";
+
+// item_id の無い item の id に使うリンクハッシュの桁数
+const LINK_HASH_LEN: usize = 16;
+
+// Atom entry の id の付け方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryIdMode {
+    /// 公開年 (無ければ初回観測の年) と item_id から作る。年が変わっても変わらない
+    #[default]
+    Stable,
+    /// id を記録していない既存 item には旧形式 (実行した年) の id を固定して使う。
+    /// 新しく入った item と item_id の無い item は stable と同じ
+    Legacy,
+}
+
+/// 年をまたいでも変わらない entry の id。item_id が無ければ記事 URL のハッシュを使う
+pub fn stable_entry_id(item: &StoredItem) -> String {
+    let year = [item.published.as_deref(), Some(item.first_seen.as_str())]
+        .into_iter()
+        .flatten()
+        .find_map(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.year());
+    let specific = match &item.item_id {
+        Some(id) => id.clone(),
+        None => format!("link-{}", &link_hash(&item.link)[..LINK_HASH_LEN]),
+    };
+    match year {
+        Some(year) => format!("tag:qiita.com,{}:{}", year, specific),
+        // 日付が読めない壊れた item は記事 URL 自体を id にする
+        None => canonicalize_url(&item.link),
+    }
+}
+
+/// 以前の形式の id (実行した年が入るため毎年変わる)。item_id が無い item は旧形式では
+/// 記事ごとに区別できないため stable と同じ id にする
+pub fn legacy_entry_id(item: &StoredItem, now: DateTime<Utc>) -> String {
+    match &item.item_id {
+        Some(id) => format!("tag:qiita.com,{}:{}", now.format("%Y"), id),
+        None => stable_entry_id(item),
+    }
+}
@@ -5,6 +5,9 @@ pub enum ErrorKind {
     Config,
     Network,
     Feed,
+    State,
+    Locked,
+    NotFound,
 }
 
 #[derive(Debug, Clone)]
@@ -35,11 +38,41 @@ impl AppError {
         }
     }
 
+    /// state ファイルの読み込み・パースに失敗した (破損の可能性がある)
+    pub fn state(message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::State,
+            message: message.into(),
+        }
+    }
+
+    /// 別プロセスが state のロックを保持している
+    pub fn locked(message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Locked,
+            message: message.into(),
+        }
+    }
+
+    /// Qiita API が 404 を返した (記事が削除された可能性がある)
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::NotFound,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.kind, ErrorKind::NotFound)
+    }
+
     pub fn exit_code(&self) -> i32 {
         match self.kind {
             ErrorKind::Config => 2,
-            ErrorKind::Network => 3,
+            ErrorKind::Network | ErrorKind::NotFound => 3,
             ErrorKind::Feed => 4,
+            ErrorKind::State => 5,
+            ErrorKind::Locked => 6,
         }
     }
 }
@@ -0,0 +1,69 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+// tiers / tag_feeds / digests / milestone_feed に共通する出力先と表示の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtraFeedOptions {
+    /// 出力先 (feed_path と同じくサイト内のパス)。未指定なら feed ごとの既定の場所
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 未指定なら feed ごとの既定のタイトル
+    #[serde(default)]
+    pub title: Option<String>,
+    /// 未指定なら site_description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 未指定なら max_feed_entries
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+}
+
+impl ExtraFeedOptions {
+    pub fn max_entries(&self, default_max: usize) -> usize {
+        self.max_entries.unwrap_or(default_max)
+    }
+
+    /// section は config のキー (tiers など)、name はエラーに出す feed の名前
+    pub fn validate(&self, section: &str, name: &str) -> Result<(), AppError> {
+        if self.max_entries == Some(0) {
+            return Err(AppError::config(format!(
+                "{} の max_entries は 1 以上で指定してください: {}",
+                section, name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// feed.xml と同じ state から作る追加の feed
+pub trait ExtraFeed {
+    /// dry-run の表示に使う種類 (tier-feed など)
+    fn kind(&self) -> &'static str;
+    fn name(&self) -> String;
+    /// site_url 未設定時の feed id に使う名前
+    fn id_name(&self) -> String;
+    fn options(&self) -> &ExtraFeedOptions;
+    fn default_path(&self) -> String;
+    fn default_title(&self, site_title: &str) -> String;
+
+    fn output_path(&self) -> String {
+        self.options()
+            .path
+            .clone()
+            .unwrap_or_else(|| self.default_path())
+    }
+
+    fn feed_title(&self, site_title: &str) -> String {
+        self.options()
+            .title
+            .clone()
+            .unwrap_or_else(|| self.default_title(site_title))
+    }
+
+    fn feed_description(&self, site_description: &str) -> String {
+        self.options()
+            .description
+            .clone()
+            .unwrap_or_else(|| site_description.to_string())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::canonical::canonicalize_url;
+use sha2::{Digest, Sha256};
+
+/// タイトルと本文の要約から内容のハッシュを作る。空白の違いだけの編集は同じ値になる
+pub fn content_hash(title: &str, summary: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(normalize_text(title).as_bytes());
+    // タイトルと本文の境界をずらしただけの変更を区別する
+    hasher.update([0u8]);
+    hasher.update(normalize_text(summary.unwrap_or_default()).as_bytes());
+    to_hex(&hasher.finalize())
+}
+
+/// 記事 URL のハッシュ。クエリ違いなど同じ記事を指す URL は同じ値になる
+pub fn link_hash(link: &str) -> String {
+    to_hex(&Sha256::digest(canonicalize_url(link).as_bytes()))
+}
+
+fn normalize_text(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
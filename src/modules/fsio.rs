@@ -0,0 +1,116 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 一時ファイルへ書き込んで fsync してから rename する。途中で落ちても元のファイルは壊れない
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&parent)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "ファイル名がありません"))?;
+    let tmp_path = parent.join(format!(
+        ".{}.tmp-{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        sync_dir(&parent)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// 拡張子を保ったままバックアップ名を作る (articles.json -> articles.bak1.json,
+/// articles.json.gz -> articles.bak1.json.gz)
+pub fn backup_path(path: &Path, generation: usize) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (stem, ext) = split_extension(&name);
+    let name = match ext {
+        Some(ext) => format!("{}.bak{}.{}", stem, generation, ext),
+        None => format!("{}.bak{}", stem, generation),
+    };
+    path.with_file_name(name)
+}
+
+// .gz は直前の拡張子と合わせて 1 つの拡張子として扱う
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    let Some(dot) = name.rfind('.').filter(|&i| i > 0) else {
+        return (name, None);
+    };
+    if name[dot + 1..].eq_ignore_ascii_case("gz") {
+        if let Some(inner) = name[..dot].rfind('.').filter(|&i| i > 0) {
+            return (&name[..inner], Some(&name[inner + 1..]));
+        }
+    }
+    (&name[..dot], Some(&name[dot + 1..]))
+}
+
+/// 既存のファイルを bak1 に退避し、古いバックアップを 1 つずつずらす。keep を超えた分は消える
+pub fn rotate_backups(path: &Path, keep: usize) -> io::Result<()> {
+    rotate_backup_group(path, keep, |p| vec![p.to_path_buf()])
+}
+
+/// 本体と付随ファイルをまとめてローテーションする。files は本体 (またはそのバックアップ) のパスから
+/// 同じ世代に属するファイル一覧を返す
+pub fn rotate_backup_group(
+    path: &Path,
+    keep: usize,
+    files: impl Fn(&Path) -> Vec<PathBuf>,
+) -> io::Result<()> {
+    if keep == 0 || !path.exists() {
+        return Ok(());
+    }
+    for generation in (1..keep).rev() {
+        let from = files(&backup_path(path, generation));
+        let to = files(&backup_path(path, generation + 1));
+        for (from, to) in from.iter().zip(to.iter()) {
+            if from.exists() {
+                fs::rename(from, to)?;
+            }
+        }
+    }
+    // rename だと保存失敗時に本体が無くなるためコピーで退避する
+    for (from, to) in files(path).iter().zip(files(&backup_path(path, 1)).iter()) {
+        if from.exists() {
+            let content = fs::read(from)?;
+            write_atomic(to, &content)?;
+        }
+    }
+    Ok(())
+}
+
+/// 存在するバックアップを新しい順に返す
+pub fn existing_backups(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|generation| backup_path(path, generation))
+        .take_while(|p| p.exists())
+        .collect()
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
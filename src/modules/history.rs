@@ -0,0 +1,70 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 直近この時間までは 1 時間ごと、それより古いものは 1 日ごとに間引く
+const HOURLY_WINDOW_HOURS: i64 = 48;
+
+// likes / stocks の観測値 1 件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LikesSample {
+    pub at: String,
+    pub likes: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stocks: Option<u32>,
+}
+
+impl LikesSample {
+    pub fn at_time(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.at)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// 観測値を追加して間引く
+pub fn record_sample(
+    history: &mut Vec<LikesSample>,
+    likes: u32,
+    stocks: Option<u32>,
+    now: DateTime<Utc>,
+) {
+    history.push(LikesSample {
+        at: now.to_rfc3339(),
+        likes,
+        stocks,
+    });
+    downsample(history, now);
+}
+
+/// 時刻順に並べ、同じ時間帯 (直近は 1 時間、それ以前は 1 日) の中では最新の 1 件だけ残す
+pub fn downsample(history: &mut Vec<LikesSample>, now: DateTime<Utc>) {
+    history.retain(|sample| sample.at_time().is_some());
+    history.sort_by_key(|sample| sample.at_time());
+    let hourly_cutoff = now - Duration::hours(HOURLY_WINDOW_HOURS);
+    let mut kept: Vec<LikesSample> = Vec::with_capacity(history.len());
+    let mut last_bucket: Option<String> = None;
+    for sample in history.drain(..).rev() {
+        let at = sample.at_time().unwrap_or(now);
+        let bucket = if at >= hourly_cutoff {
+            at.format("h%Y-%m-%dT%H").to_string()
+        } else {
+            at.format("d%Y-%m-%d").to_string()
+        };
+        if last_bucket.as_deref() == Some(bucket.as_str()) {
+            continue;
+        }
+        last_bucket = Some(bucket);
+        kept.push(sample);
+    }
+    kept.reverse();
+    *history = kept;
+}
+
+/// since 以降の likes の増分を返す。比較できる観測値が無い場合は None
+pub fn likes_growth_since(history: &[LikesSample], since: DateTime<Utc>) -> Option<i64> {
+    let latest = history.last()?;
+    let base = history
+        .iter()
+        .find(|sample| sample.at_time().is_some_and(|at| at >= since))?;
+    Some(latest.likes as i64 - base.likes as i64)
+}
@@ -7,9 +7,33 @@ pub struct IndexPage {
     pub updated: DateTime<FixedOffset>,
     pub min_likes: u32,
     pub feed_source: String,
+    /// feed.xml 以外に公開している feed (タグ別など)
+    pub extra_feeds: Vec<FeedLink>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedLink {
+    pub title: String,
+    pub url: String,
 }
 
 pub fn build_index_html(page: &IndexPage) -> String {
+    let extra_feeds = if page.extra_feeds.is_empty() {
+        String::new()
+    } else {
+        let items: String = page
+            .extra_feeds
+            .iter()
+            .map(|feed| {
+                format!(
+                    "    <li><a href=\"{}\">{}</a></li>\n",
+                    escape_html(&feed.url),
+                    escape_html(&feed.title)
+                )
+            })
+            .collect();
+        format!("  <ul>\n{}  </ul>\n", items)
+    };
     format!(
         r#"<!doctype html>
 <html lang="ja">
@@ -26,7 +50,7 @@ pub fn build_index_html(page: &IndexPage) -> String {
   <h1>{title}</h1>
   <p>{description}</p>
   <p><a href="{feed_url}">feed.xml</a></p>
-  <div class="meta">
+{extra_feeds}  <div class="meta">
     <p>最終更新: {updated}</p>
     <p>min_likes: {min_likes}</p>
     <p>source: {feed_source}</p>
@@ -37,13 +61,14 @@ pub fn build_index_html(page: &IndexPage) -> String {
         title = escape_html(&page.title),
         description = escape_html(&page.description),
         feed_url = escape_html(&page.feed_url),
+        extra_feeds = extra_feeds,
         updated = page.updated.to_rfc3339(),
         min_likes = page.min_likes,
         feed_source = escape_html(&page.feed_source)
     )
 }
 
-fn escape_html(value: &str) -> String {
+pub fn escape_html(value: &str) -> String {
     // 最低限の HTML エスケープ
     value
         .replace('&', "&amp;")
@@ -0,0 +1,209 @@
+use crate::state::{StateStore, StoredItem};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+const CSV_COLUMNS: [&str; 14] = [
+    "key",
+    "item_id",
+    "title",
+    "link",
+    "author_name",
+    "likes_count",
+    "likes_lower_bound",
+    "stocks_count",
+    "published",
+    "updated",
+    "first_seen",
+    "qualified_at",
+    "last_seen",
+    "tags",
+];
+
+const TOP_TAGS: usize = 10;
+
+// state 一覧の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSort {
+    Likes,
+    Published,
+    Qualified,
+    FirstSeen,
+    LastSeen,
+}
+
+// state 一覧の絞り込み条件
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    pub min_likes: Option<u32>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+// state 全体の集計
+#[derive(Debug, Clone, Serialize)]
+pub struct StateStats {
+    pub items: usize,
+    pub watchlist: usize,
+    pub likes_total: u64,
+    pub likes_max: u32,
+    pub likes_median: u32,
+    pub lower_bound_items: usize,
+    pub oldest_published: Option<String>,
+    pub newest_published: Option<String>,
+    pub oldest_last_seen: Option<String>,
+    pub top_tags: Vec<TagCount>,
+}
+
+/// 条件に合う item を並べて返す。同順位は key 順
+pub fn list_items<'a>(
+    store: &'a StateStore,
+    filter: &ListFilter,
+    sort: ListSort,
+) -> Vec<&'a StoredItem> {
+    let mut items: Vec<&StoredItem> = store
+        .items
+        .values()
+        .filter(|item| filter.min_likes.is_none_or(|min| item.likes_count >= min))
+        .filter(|item| {
+            filter
+                .tag
+                .as_deref()
+                .is_none_or(|tag| item.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        })
+        .filter(|item| {
+            filter
+                .author
+                .as_deref()
+                .is_none_or(|author| item.author_name.as_deref() == Some(author))
+        })
+        .collect();
+    items.sort_by(|a, b| compare_for_list(a, b, sort).then_with(|| a.key.cmp(&b.key)));
+    if let Some(limit) = filter.limit {
+        items.truncate(limit);
+    }
+    items
+}
+
+fn compare_for_list(a: &StoredItem, b: &StoredItem, sort: ListSort) -> Ordering {
+    // いずれも新しい / 多い順
+    match sort {
+        ListSort::Likes => b.likes_count.cmp(&a.likes_count),
+        ListSort::Published => {
+            parse_time(b.published.as_deref()).cmp(&parse_time(a.published.as_deref()))
+        }
+        ListSort::Qualified => b.qualified_time().cmp(&a.qualified_time()),
+        ListSort::FirstSeen => b.first_seen_time().cmp(&a.first_seen_time()),
+        ListSort::LastSeen => parse_time(Some(&b.last_seen)).cmp(&parse_time(Some(&a.last_seen))),
+    }
+}
+
+pub fn compute_stats(store: &StateStore) -> StateStats {
+    let mut likes: Vec<u32> = store.items.values().map(|item| item.likes_count).collect();
+    likes.sort_unstable();
+    let likes_median = if likes.is_empty() {
+        0
+    } else {
+        likes[likes.len() / 2]
+    };
+
+    let published: Vec<(DateTime<Utc>, &str)> = store
+        .items
+        .values()
+        .filter_map(|item| {
+            let raw = item.published.as_deref()?;
+            parse_time(Some(raw)).map(|dt| (dt, raw))
+        })
+        .collect();
+    let oldest_published = published.iter().min().map(|(_, raw)| raw.to_string());
+    let newest_published = published.iter().max().map(|(_, raw)| raw.to_string());
+    let oldest_last_seen = store
+        .items
+        .values()
+        .filter_map(|item| parse_time(Some(&item.last_seen)).map(|dt| (dt, &item.last_seen)))
+        .min()
+        .map(|(_, raw)| raw.clone());
+
+    let mut tag_counts: HashMap<String, (String, usize)> = HashMap::new();
+    for item in store.items.values() {
+        for tag in &item.tags {
+            let entry = tag_counts
+                .entry(tag.to_lowercase())
+                .or_insert_with(|| (tag.clone(), 0));
+            entry.1 += 1;
+        }
+    }
+    let mut top_tags: Vec<TagCount> = tag_counts
+        .into_values()
+        .map(|(tag, count)| TagCount { tag, count })
+        .collect();
+    top_tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    top_tags.truncate(TOP_TAGS);
+
+    StateStats {
+        items: store.items.len(),
+        watchlist: store.watchlist.len(),
+        likes_total: likes.iter().map(|&l| l as u64).sum(),
+        likes_max: likes.last().copied().unwrap_or(0),
+        likes_median,
+        lower_bound_items: store
+            .items
+            .values()
+            .filter(|item| item.likes_lower_bound)
+            .count(),
+        oldest_published,
+        newest_published,
+        oldest_last_seen,
+        top_tags,
+    }
+}
+
+/// item 一覧を CSV (RFC 4180) にする。tags は ; 区切り
+pub fn export_csv(items: &[&StoredItem]) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push_str("\r\n");
+    for item in items {
+        let fields = [
+            item.key.clone(),
+            item.item_id.clone().unwrap_or_default(),
+            item.title.clone(),
+            item.link.clone(),
+            item.author_name.clone().unwrap_or_default(),
+            item.likes_count.to_string(),
+            item.likes_lower_bound.to_string(),
+            item.stocks_count.map(|s| s.to_string()).unwrap_or_default(),
+            item.published.clone().unwrap_or_default(),
+            item.updated.clone().unwrap_or_default(),
+            item.first_seen.clone(),
+            item.qualified_at.clone(),
+            item.last_seen.clone(),
+            item.tags.join(";"),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_time(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
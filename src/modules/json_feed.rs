@@ -0,0 +1,111 @@
+use crate::atom::{FeedEntry, FeedInfo};
+use crate::error::AppError;
+use serde::Serialize;
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[derive(Debug, Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    favicon: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedItem<'a> {
+    id: &'a str,
+    url: &'a str,
+    title: &'a str,
+    content_html: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    date_modified: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor<'a>>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    #[serde(rename = "_qiita", skip_serializing_if = "Option::is_none")]
+    qiita: Option<QiitaExtension<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedAuthor<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+}
+
+// JSON Feed の拡張フィールド (先頭が _ のキーはリーダーに無視される)
+#[derive(Debug, Serialize)]
+struct QiitaExtension<'a> {
+    about: &'static str,
+    item_id: Option<&'a str>,
+    likes: u32,
+    likes_lower_bound: bool,
+    stocks: Option<u32>,
+    tags: &'a [String],
+}
+
+/// FeedInfo を JSON Feed 1.1 として出力する。self_url は JSON Feed 自身の URL
+pub fn build_json_feed(feed: &FeedInfo, self_url: &str) -> Result<String, AppError> {
+    let json_feed = JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: &feed.title,
+        home_page_url: &feed.index_url,
+        feed_url: self_url,
+        description: feed.description.trim(),
+        // JSON Feed の icon は大きな画像、favicon は小さな画像
+        icon: non_empty(feed.logo.as_deref()),
+        favicon: non_empty(feed.icon.as_deref()),
+        language: non_empty(feed.lang.as_deref()),
+        items: feed.entries.iter().map(build_item).collect(),
+    };
+    serde_json::to_string_pretty(&json_feed)
+        .map_err(|e| AppError::feed(format!("JSON Feed 変換失敗: {}", e)))
+}
+
+fn build_item(entry: &FeedEntry) -> JsonFeedItem<'_> {
+    let authors = entry
+        .author
+        .as_ref()
+        .filter(|author| !author.name.trim().is_empty())
+        .map(|author| JsonFeedAuthor {
+            name: &author.name,
+            url: author.uri.as_deref(),
+        })
+        .into_iter()
+        .collect();
+    let tags = entry.qiita.as_ref().map_or(&[][..], |q| q.tags.as_slice());
+    JsonFeedItem {
+        id: &entry.id,
+        url: &entry.link,
+        title: &entry.title,
+        content_html: &entry.summary_html,
+        date_published: entry.published.map(|dt| dt.to_rfc3339()),
+        date_modified: entry.updated.to_rfc3339(),
+        authors,
+        tags,
+        qiita: entry.qiita.as_ref().map(|q| QiitaExtension {
+            about: "https://qiita.com/",
+            item_id: q.item_id.as_deref(),
+            likes: q.likes,
+            likes_lower_bound: q.likes_lower_bound,
+            stocks: q.stocks,
+            tags: &q.tags,
+        }),
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.filter(|v| !v.trim().is_empty())
+}
@@ -0,0 +1,85 @@
+use crate::error::AppError;
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL_MS: u64 = 500;
+
+/// state と同じ場所に置くロックファイルの排他ロック。drop で解放される
+#[derive(Debug)]
+pub struct StateLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl StateLock {
+    /// ロックを取得する。timeout が 0 なら待たずに失敗する
+    pub fn acquire(state_path: &Path, timeout: Duration) -> Result<Self, AppError> {
+        let path = lock_path(state_path);
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| AppError::feed(format!("ロックディレクトリ作成失敗: {}", e)))?;
+            }
+        }
+        // ロックファイルは消さない (消すと別プロセスが別 inode をロックできてしまう)
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| AppError::feed(format!("ロックファイル作成失敗: {}", e)))?;
+
+        let started = Instant::now();
+        let mut waiting_logged = false;
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(Self { file, path }),
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    if started.elapsed() >= timeout {
+                        return Err(AppError::locked(format!(
+                            "別のプロセスが state を使用中です: lock={} waited={}s",
+                            path.display(),
+                            started.elapsed().as_secs()
+                        )));
+                    }
+                    if !waiting_logged {
+                        eprintln!(
+                            "state のロック解放を待っています: lock={} timeout={}s",
+                            path.display(),
+                            timeout.as_secs()
+                        );
+                        waiting_logged = true;
+                    }
+                    sleep(Duration::from_millis(POLL_INTERVAL_MS).min(timeout));
+                }
+                Err(e) => {
+                    return Err(AppError::feed(format!("ロック取得失敗: {}", e)));
+                }
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// articles.json -> articles.json.lock
+pub fn lock_path(state_path: &Path) -> PathBuf {
+    let mut name = state_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    name.push(".lock");
+    state_path.with_file_name(name)
+}
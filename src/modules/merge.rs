@@ -0,0 +1,170 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+// フィールドごとの統合方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldPolicy {
+    /// 常に新しい値で置き換える (None でも置き換える)
+    Replace,
+    /// 新しい値が None / 空なら既存の値を残す
+    KeepIfMissing,
+    /// 大きい方を採用する (カウンタ専用)
+    Max,
+}
+
+// merge_from_feed で使うフィールド単位の統合ポリシー
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergePolicy {
+    pub title: FieldPolicy,
+    pub summary: FieldPolicy,
+    pub published: FieldPolicy,
+    pub updated: FieldPolicy,
+    pub author_name: FieldPolicy,
+    pub tags: FieldPolicy,
+    pub likes_count: FieldPolicy,
+    pub stocks_count: FieldPolicy,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        Self {
+            title: FieldPolicy::Replace,
+            summary: FieldPolicy::KeepIfMissing,
+            published: FieldPolicy::KeepIfMissing,
+            updated: FieldPolicy::KeepIfMissing,
+            author_name: FieldPolicy::KeepIfMissing,
+            tags: FieldPolicy::KeepIfMissing,
+            likes_count: FieldPolicy::Max,
+            stocks_count: FieldPolicy::Max,
+        }
+    }
+}
+
+impl MergePolicy {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let text_fields = [
+            ("title", self.title),
+            ("summary", self.summary),
+            ("published", self.published),
+            ("updated", self.updated),
+            ("author_name", self.author_name),
+            ("tags", self.tags),
+        ];
+        for (field, policy) in text_fields {
+            if policy == FieldPolicy::Max {
+                return Err(AppError::config(format!(
+                    "merge_policy.{} に max は指定できません (likes_count / stocks_count 専用)",
+                    field
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 統合で値が変わったフィールド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+// item ごとの統合結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    pub key: String,
+    pub is_new: bool,
+    pub changes: Vec<FieldChange>,
+}
+
+impl MergeReport {
+    pub fn is_changed(&self) -> bool {
+        self.is_new || !self.changes.is_empty()
+    }
+}
+
+/// 単一値のフィールドを統合する
+pub fn merge_value<T: Clone + Ord + Display>(
+    field: &'static str,
+    policy: FieldPolicy,
+    previous: Option<&T>,
+    incoming: T,
+    changes: &mut Vec<FieldChange>,
+) -> T {
+    let Some(prev) = previous else {
+        return incoming;
+    };
+    let merged = match policy {
+        FieldPolicy::Max => prev.clone().max(incoming),
+        _ => incoming,
+    };
+    record_change(field, Some(prev), Some(&merged), changes);
+    merged
+}
+
+/// Option のフィールドを統合する。previous が None なら新規 item
+pub fn merge_option<T: Clone + Ord + Display>(
+    field: &'static str,
+    policy: FieldPolicy,
+    previous: Option<&Option<T>>,
+    incoming: Option<T>,
+    changes: &mut Vec<FieldChange>,
+) -> Option<T> {
+    let Some(prev) = previous else {
+        return incoming;
+    };
+    let merged = match policy {
+        FieldPolicy::Replace => incoming,
+        FieldPolicy::KeepIfMissing => incoming.or_else(|| prev.clone()),
+        FieldPolicy::Max => match (prev.clone(), incoming) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => b.or(a),
+        },
+    };
+    record_change(field, prev.as_ref(), merged.as_ref(), changes);
+    merged
+}
+
+/// タグ一覧を統合する。空の一覧は「取得できなかった」とみなす
+pub fn merge_tags(
+    policy: FieldPolicy,
+    previous: Option<&Vec<String>>,
+    incoming: Vec<String>,
+    changes: &mut Vec<FieldChange>,
+) -> Vec<String> {
+    let Some(prev) = previous else {
+        return incoming;
+    };
+    let merged = if incoming.is_empty() && policy != FieldPolicy::Replace {
+        prev.clone()
+    } else {
+        incoming
+    };
+    if *prev != merged {
+        changes.push(FieldChange {
+            field: "tags",
+            before: Some(prev.join(",")),
+            after: Some(merged.join(",")),
+        });
+    }
+    merged
+}
+
+fn record_change<T: PartialEq + Display>(
+    field: &'static str,
+    before: Option<&T>,
+    after: Option<&T>,
+    changes: &mut Vec<FieldChange>,
+) {
+    if before != after {
+        changes.push(FieldChange {
+            field,
+            before: before.map(|v| v.to_string()),
+            after: after.map(|v| v.to_string()),
+        });
+    }
+}
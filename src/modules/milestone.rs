@@ -0,0 +1,187 @@
+use crate::atom::{FeedAuthor, FeedEntry};
+use crate::canonical::extract_username;
+use crate::curation::CuratedItem;
+use crate::entry_id::stable_entry_id;
+use crate::error::AppError;
+use crate::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use crate::html::escape_html;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// milestone_feed の thresholds 未指定時の節目
+pub const DEFAULT_MILESTONES: [u32; 3] = [100, 500, 1000];
+
+// item の likes が節目を超えたことを一度だけ記録する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MilestoneEvent {
+    pub threshold: u32,
+    /// 検出した時点の likes
+    pub likes: u32,
+    pub reached_at: String,
+}
+
+/// 最後に確定した likes (previous_likes) から new_likes への変化で超えた節目を返す。
+/// new_likes は下限値でもよい (previous_likes < 節目 <= 下限値なら確かに超えている)。
+/// 記録済みの節目と、確定した以前の値が無い場合 (本当に超えたのか分からない) は含めない
+pub fn detect_milestones(
+    thresholds: &[u32],
+    previous_likes: Option<u32>,
+    new_likes: u32,
+    recorded: &[MilestoneEvent],
+    now: DateTime<Utc>,
+) -> Vec<MilestoneEvent> {
+    let Some(previous_likes) = previous_likes else {
+        return Vec::new();
+    };
+    let mut thresholds: Vec<u32> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| previous_likes < *t && *t <= new_likes)
+        .filter(|t| !recorded.iter().any(|e| e.threshold == *t))
+        .collect();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    thresholds
+        .into_iter()
+        .map(|threshold| MilestoneEvent {
+            threshold,
+            likes: new_likes,
+            reached_at: now.to_rfc3339(),
+        })
+        .collect()
+}
+
+/// 同じ item の記録をまとめる。同じ節目は早い方を残す
+pub fn merge_milestones(a: Vec<MilestoneEvent>, b: Vec<MilestoneEvent>) -> Vec<MilestoneEvent> {
+    let mut merged: Vec<MilestoneEvent> = Vec::new();
+    for event in a.into_iter().chain(b) {
+        match merged.iter_mut().find(|e| e.threshold == event.threshold) {
+            Some(existing) => {
+                if reached_time(&event) < reached_time(existing) {
+                    *existing = event;
+                }
+            }
+            None => merged.push(event),
+        }
+    }
+    merged.sort_by_key(|e| e.threshold);
+    merged
+}
+
+fn reached_time(event: &MilestoneEvent) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&event.reached_at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+// 節目を超えるたびに 1 entry を出す feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneFeed {
+    #[serde(default = "default_thresholds")]
+    pub thresholds: Vec<u32>,
+    /// path 未指定なら feeds/milestones.xml、title 未指定なら「<site_title> (milestones)」
+    #[serde(flatten)]
+    pub options: ExtraFeedOptions,
+}
+
+fn default_thresholds() -> Vec<u32> {
+    DEFAULT_MILESTONES.to_vec()
+}
+
+impl ExtraFeed for MilestoneFeed {
+    fn kind(&self) -> &'static str {
+        "milestone-feed"
+    }
+
+    fn name(&self) -> String {
+        "milestones".to_string()
+    }
+
+    fn id_name(&self) -> String {
+        format!(
+            "qiita-feed:milestones:{}",
+            self.output_path().trim_start_matches('/')
+        )
+    }
+
+    fn options(&self) -> &ExtraFeedOptions {
+        &self.options
+    }
+
+    fn default_path(&self) -> String {
+        "feeds/milestones.xml".to_string()
+    }
+
+    fn default_title(&self, site_title: &str) -> String {
+        format!("{} (milestones)", site_title)
+    }
+}
+
+impl MilestoneFeed {
+    /// 記録済みの節目ごとに entry を作り、新しい順に最大 max_entries 件返す
+    pub fn build_entries(&self, items: &[CuratedItem], default_max: usize) -> Vec<FeedEntry> {
+        let mut events: Vec<(&CuratedItem, &MilestoneEvent, DateTime<Utc>)> = items
+            .iter()
+            .flat_map(|curated| {
+                curated
+                    .item
+                    .milestones
+                    .iter()
+                    .filter(|e| self.thresholds.contains(&e.threshold))
+                    .filter_map(move |e| reached_time(e).map(|t| (curated, e, t)))
+            })
+            .collect();
+        events.sort_by(|a, b| {
+            b.2.cmp(&a.2)
+                .then_with(|| b.1.threshold.cmp(&a.1.threshold))
+                .then_with(|| a.0.item.key.cmp(&b.0.item.key))
+        });
+        events
+            .into_iter()
+            .take(self.options.max_entries(default_max))
+            .map(|(curated, event, reached)| build_entry(curated, event, reached))
+            .collect()
+    }
+}
+
+fn build_entry(curated: &CuratedItem, event: &MilestoneEvent, reached: DateTime<Utc>) -> FeedEntry {
+    let item = &curated.item;
+    // item の entry id に節目を付ける (同じ item・同じ節目なら常に同じ)
+    let item_id = item
+        .entry_id
+        .clone()
+        .unwrap_or_else(|| stable_entry_id(item));
+    let reached = reached.fixed_offset();
+    FeedEntry {
+        id: format!("{}:likes-{}", item_id, event.threshold),
+        title: format!("{} likes: {}", event.threshold, item.title),
+        link: item.link.clone(),
+        updated: reached,
+        published: Some(reached),
+        author: item.author_name.clone().map(|name| FeedAuthor {
+            name,
+            uri: extract_username(&item.link).map(|user| format!("https://qiita.com/{}", user)),
+        }),
+        summary_html: format!(
+            "Likes: {} (reached {})<br/><a href=\"{}\">{}</a>",
+            event.likes,
+            event.threshold,
+            escape_html(&item.link),
+            escape_html(&item.title)
+        ),
+        source: None,
+        qiita: None,
+    }
+}
+
+pub fn validate_milestone_feed(feed: &MilestoneFeed) -> Result<(), AppError> {
+    if feed.thresholds.is_empty() {
+        return Err(AppError::config("milestone_feed の thresholds が空です"));
+    }
+    if feed.thresholds.contains(&0) {
+        return Err(AppError::config(
+            "milestone_feed の thresholds は 1 以上で指定してください",
+        ));
+    }
+    feed.options.validate("milestone_feed", &feed.name())
+}
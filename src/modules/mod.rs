@@ -1,6 +1,26 @@
 pub mod atom;
+pub mod canonical;
+pub mod compress;
 pub mod config;
+pub mod curation;
+pub mod digest;
+pub mod entry_id;
 pub mod error;
+pub mod extra_feed;
+pub mod fingerprint;
+pub mod fsio;
+pub mod history;
 pub mod html;
+pub mod inspect;
+pub mod json_feed;
+pub mod lock;
+pub mod merge;
+pub mod milestone;
+pub mod prune;
 pub mod qiita_api;
+pub mod rss;
+pub mod schema;
 pub mod state;
+pub mod storage;
+pub mod tag_feed;
+pub mod tier;
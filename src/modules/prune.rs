@@ -0,0 +1,148 @@
+use crate::error::AppError;
+use crate::state::{parse_datetime, StoredItem};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 保存済み item を残すか消すかを決めるルール。上から順に評価し、最初に判定したルールに従う
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PruneRule {
+    /// pinned の item は常に残す (max_items による削除からも守る)
+    Pinned,
+    /// Qiita 側で削除された item を grace_days 経過後に消す
+    Removed {
+        #[serde(default)]
+        grace_days: u32,
+    },
+    /// likes が min_likes 以上の item は last_seen から keep_days の間残し、過ぎたら消す
+    LikesTier { min_likes: u32, keep_days: u32 },
+    /// 公開から max_days を超えた item を消す
+    PublishedAge { max_days: u32 },
+    /// last_seen から max_days を超えた item を消す
+    LastSeen { max_days: u32 },
+}
+
+// ルールの判定結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneAction {
+    Keep,
+    Remove,
+}
+
+impl PruneRule {
+    /// 判定できない item は None を返し、次のルールに任せる
+    pub fn evaluate(&self, item: &StoredItem, now: DateTime<Utc>) -> Option<PruneAction> {
+        match self {
+            PruneRule::Pinned => item.pinned.then_some(PruneAction::Keep),
+            PruneRule::Removed { grace_days } => {
+                let removed_at = item.removed_at.as_deref().and_then(parse_datetime)?;
+                (now - removed_at >= Duration::days(*grace_days as i64))
+                    .then_some(PruneAction::Remove)
+            }
+            PruneRule::LikesTier {
+                min_likes,
+                keep_days,
+            } => {
+                if item.likes_count < *min_likes {
+                    return None;
+                }
+                if older_than(Some(&item.last_seen), *keep_days, now) {
+                    Some(PruneAction::Remove)
+                } else {
+                    Some(PruneAction::Keep)
+                }
+            }
+            PruneRule::PublishedAge { max_days } => {
+                // 公開日時が無い item は判定しない
+                let published = item.published.as_deref().and_then(parse_datetime)?;
+                (published < now - Duration::days(*max_days as i64)).then_some(PruneAction::Remove)
+            }
+            PruneRule::LastSeen { max_days } => {
+                older_than(Some(&item.last_seen), *max_days, now).then_some(PruneAction::Remove)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        let days = match self {
+            PruneRule::LikesTier { keep_days, .. } => Some(("likes_tier.keep_days", *keep_days)),
+            PruneRule::PublishedAge { max_days } => Some(("published_age.max_days", *max_days)),
+            PruneRule::LastSeen { max_days } => Some(("last_seen.max_days", *max_days)),
+            PruneRule::Pinned | PruneRule::Removed { .. } => None,
+        };
+        if let Some((field, 0)) = days {
+            return Err(AppError::config(format!(
+                "prune_rules の {} は 1 以上で指定してください",
+                field
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PruneRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneRule::Pinned => write!(f, "pinned"),
+            PruneRule::Removed { grace_days } => write!(f, "removed(grace_days={})", grace_days),
+            PruneRule::LikesTier {
+                min_likes,
+                keep_days,
+            } => write!(
+                f,
+                "likes_tier(min_likes={}, keep_days={})",
+                min_likes, keep_days
+            ),
+            PruneRule::PublishedAge { max_days } => {
+                write!(f, "published_age(max_days={})", max_days)
+            }
+            PruneRule::LastSeen { max_days } => write!(f, "last_seen(max_days={})", max_days),
+        }
+    }
+}
+
+/// prune_rules 未指定時のルール。従来の max_stored_days による削除と同じ
+pub fn default_prune_rules(max_stored_days: u32) -> Vec<PruneRule> {
+    vec![
+        PruneRule::Pinned,
+        PruneRule::LastSeen {
+            max_days: max_stored_days,
+        },
+    ]
+}
+
+// 削除対象の item と、削除を決めたルール
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PruneRemoval {
+    pub key: String,
+    pub title: String,
+    pub rule: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    pub removed: Vec<PruneRemoval>,
+}
+
+impl PruneReport {
+    /// ルールごとの削除件数 (ルールの登場順)
+    pub fn counts_by_rule(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for removal in &self.removed {
+            match counts.iter_mut().find(|(rule, _)| *rule == removal.rule) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((removal.rule.clone(), 1)),
+            }
+        }
+        counts
+    }
+}
+
+fn older_than(value: Option<&str>, days: u32, now: DateTime<Utc>) -> bool {
+    // 時刻が読めない item は古いものとして扱う
+    match value.and_then(parse_datetime) {
+        Some(dt) => dt < now - Duration::days(days as i64),
+        None => true,
+    }
+}
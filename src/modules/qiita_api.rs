@@ -1,8 +1,10 @@
+use crate::canonical::extract_item_id;
 use crate::error::AppError;
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderValue, ACCEPT};
 use reqwest::StatusCode;
 use roxmltree::Document;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use std::thread::sleep;
 use std::time::Duration;
@@ -21,12 +23,34 @@ pub struct QiitaItem {
     pub published: Option<String>,
     pub updated: Option<String>,
     pub author_name: Option<String>,
+    pub tags: Vec<String>,
     pub likes_count: u32,
+    /// true の場合 likes_count は「N 以上」であることのみを表す
+    pub likes_lower_bound: bool,
+    /// threshold で打ち切らずに集計した (max_pages で頭打ちの場合も含む)
+    pub likes_full_scan: bool,
+    pub stocks_count: Option<u32>,
+}
+
+/// likes 集計の結果。途中で打ち切った場合は lower_bound が true になる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LikesCount {
+    pub count: u32,
+    pub lower_bound: bool,
+    /// 集計に使った API リクエスト数
+    pub requests: u32,
+    /// threshold で打ち切らずにページを辿った。max_pages で頭打ちなら lower_bound と両立する
+    pub full_scan: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct LikeEntry {}
 
+#[derive(Debug, Deserialize)]
+struct ItemDetail {
+    stocks_count: u32,
+}
+
 pub struct QiitaClient {
     client: Client,
     token: Option<String>,
@@ -106,22 +130,36 @@ impl QiitaClient {
         per_page: u32,
         max_pages: u32,
     ) -> Result<u32, AppError> {
-        let mut total = 0u32;
-        for page in 1..=max_pages {
-            // すべてのページを辿って likes 数を集計する
-            let likes = self.fetch_likes_page(item_id, per_page, page)?;
-            total = total.saturating_add(likes.len() as u32);
-            if likes.len() < per_page as usize {
-                return Ok(total);
-            }
+        let counted = self.fetch_likes(item_id, per_page, max_pages, None)?;
+        Ok(counted.count)
+    }
+
+    /// likes 数を集計する。threshold を指定するとそこに達した時点で打ち切る
+    pub fn fetch_likes(
+        &self,
+        item_id: &str,
+        per_page: u32,
+        max_pages: u32,
+        threshold: Option<u32>,
+    ) -> Result<LikesCount, AppError> {
+        let counted = count_likes_pages(per_page, max_pages, threshold, |page| {
+            self.fetch_likes_page(item_id, per_page, page)
+                .map(|likes| likes.len())
+        })?;
+        if counted.lower_bound && counted.full_scan {
+            eprintln!(
+                "likes が上限に達しました: item_id={} total>={} pages={}",
+                item_id, counted.count, max_pages
+            );
         }
-        eprintln!(
-            "likes が上限に達しました: item_id={} total>={} pages={}",
-            item_id,
-            total,
-            max_pages
-        );
-        Ok(total)
+        Ok(counted)
+    }
+
+    /// 記事の stocks 数を取得する
+    pub fn fetch_stocks_count(&self, item_id: &str) -> Result<u32, AppError> {
+        let url = format!("{}/{}", BASE_LIKES_URL, item_id);
+        let detail: ItemDetail = self.get_api_json(&url, &[], "item")?;
+        Ok(detail.stocks_count)
     }
 
     fn fetch_likes_page(
@@ -130,14 +168,24 @@ impl QiitaClient {
         per_page: u32,
         page: u32,
     ) -> Result<Vec<LikeEntry>, AppError> {
-        let mut attempt = 0;
         let url = format!("{}/{}/likes", BASE_LIKES_URL, item_id);
+        self.get_api_json(
+            &url,
+            &[("per_page", per_page.to_string()), ("page", page.to_string())],
+            "likes",
+        )
+    }
+
+    fn get_api_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        label: &str,
+    ) -> Result<T, AppError> {
+        let mut attempt = 0;
         loop {
             attempt += 1;
-            let mut request = self
-                .client
-                .get(&url)
-                .query(&[("per_page", per_page.to_string()), ("page", page.to_string())]);
+            let mut request = self.client.get(url).query(query);
             request = request.header(ACCEPT, HeaderValue::from_static("application/json"));
             if let Some(token) = &self.token {
                 request = request.bearer_auth(token);
@@ -151,16 +199,23 @@ impl QiitaClient {
                             "Qiita API が 401 を返しました。QIITA_API_TOKEN を設定してください。",
                         ));
                     }
+                    if status == StatusCode::NOT_FOUND {
+                        return Err(AppError::not_found(format!(
+                            "{} が見つかりません: url={}",
+                            label, url
+                        )));
+                    }
                     if status.is_success() {
-                        let parsed: Vec<LikeEntry> = resp.json().map_err(|e| {
-                            AppError::network(format!("likes JSON パース失敗: {}", e))
+                        let parsed: T = resp.json().map_err(|e| {
+                            AppError::network(format!("{} JSON パース失敗: {}", label, e))
                         })?;
                         return Ok(parsed);
                     }
                     if should_retry(status) && attempt < MAX_RETRIES {
                         let backoff = backoff_duration(attempt);
                         eprintln!(
-                            "likes リトライ: url={} status={} attempt={} backoff={}s",
+                            "{} リトライ: url={} status={} attempt={} backoff={}s",
+                            label,
                             url,
                             status,
                             attempt,
@@ -170,15 +225,16 @@ impl QiitaClient {
                         continue;
                     }
                     return Err(AppError::network(format!(
-                        "likes 取得失敗: url={} status={} attempt={}",
-                        url, status, attempt
+                        "{} 取得失敗: url={} status={} attempt={}",
+                        label, url, status, attempt
                     )));
                 }
                 Err(e) => {
                     if attempt < MAX_RETRIES {
                         let backoff = backoff_duration(attempt);
                         eprintln!(
-                            "likes リトライ: url={} error={} attempt={} backoff={}s",
+                            "{} リトライ: url={} error={} attempt={} backoff={}s",
+                            label,
                             url,
                             e,
                             attempt,
@@ -188,8 +244,8 @@ impl QiitaClient {
                         continue;
                     }
                     return Err(AppError::network(format!(
-                        "likes 取得失敗: url={} error={} attempt={}",
-                        url, e, attempt
+                        "{} 取得失敗: url={} error={} attempt={}",
+                        label, url, e, attempt
                     )));
                 }
             }
@@ -197,6 +253,50 @@ impl QiitaClient {
     }
 }
 
+/// fetch_page(page) が返す件数から likes 数を集計する。最終ページ (per_page 未満) まで
+/// 辿れば確定値、threshold に達したか max_pages を使い切って打ち切った場合は下限値になる
+pub fn count_likes_pages<F>(
+    per_page: u32,
+    max_pages: u32,
+    threshold: Option<u32>,
+    mut fetch_page: F,
+) -> Result<LikesCount, AppError>
+where
+    F: FnMut(u32) -> Result<usize, AppError>,
+{
+    let mut total = 0u32;
+    let mut requests = 0u32;
+    for page in 1..=max_pages {
+        // 最終ページに届くか threshold に達するまでページを辿る
+        let likes = fetch_page(page)?;
+        requests += 1;
+        total = total.saturating_add(likes as u32);
+        if likes < per_page as usize {
+            return Ok(LikesCount {
+                count: total,
+                lower_bound: false,
+                requests,
+                full_scan: true,
+            });
+        }
+        if threshold.is_some_and(|t| total >= t) {
+            // 次のページが残っているため total は下限値になる
+            return Ok(LikesCount {
+                count: total,
+                lower_bound: true,
+                requests,
+                full_scan: false,
+            });
+        }
+    }
+    Ok(LikesCount {
+        count: total,
+        lower_bound: true,
+        requests,
+        full_scan: true,
+    })
+}
+
 fn parse_feed_xml(xml: &str) -> Result<Vec<QiitaItem>, AppError> {
     let doc = Document::parse(xml)
         .map_err(|e| AppError::network(format!("Feed XML パース失敗: {}", e)))?;
@@ -232,6 +332,13 @@ fn parse_feed_xml(xml: &str) -> Result<Vec<QiitaItem>, AppError> {
             .children()
             .find(|n| n.has_tag_name("author"))
             .and_then(|n| child_text(&n, "name"));
+        let tags = entry
+            .children()
+            .filter(|n| n.has_tag_name("category"))
+            .filter_map(|n| n.attribute("term"))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
         let item_id = extract_item_id(&link);
 
         items.push(QiitaItem {
@@ -242,7 +349,11 @@ fn parse_feed_xml(xml: &str) -> Result<Vec<QiitaItem>, AppError> {
             published,
             updated,
             author_name,
+            tags,
             likes_count: 0,
+            likes_lower_bound: false,
+            likes_full_scan: false,
+            stocks_count: None,
         });
     }
     Ok(items)
@@ -256,22 +367,6 @@ fn child_text(node: &roxmltree::Node<'_, '_>, tag: &str) -> Option<String> {
         .filter(|v| !v.is_empty())
 }
 
-fn extract_item_id(link: &str) -> Option<String> {
-    let marker = "/items/";
-    let start = link.find(marker)? + marker.len();
-    let mut id_part = &link[start..];
-    if let Some(end) = id_part.find('?') {
-        id_part = &id_part[..end];
-    }
-    if let Some(end) = id_part.find('#') {
-        id_part = &id_part[..end];
-    }
-    if id_part.is_empty() {
-        return None;
-    }
-    Some(id_part.to_string())
-}
-
 fn should_retry(status: StatusCode) -> bool {
     status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
 }
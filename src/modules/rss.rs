@@ -0,0 +1,97 @@
+use crate::atom::{write_text_element, FeedInfo, GENERATOR_NAME};
+use crate::error::AppError;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::Cursor;
+
+/// FeedInfo を RSS 2.0 として出力する。self_url は RSS 自身の URL
+pub fn build_rss_xml(feed: &FeedInfo, self_url: &str) -> Result<String, AppError> {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .map_err(|e| AppError::feed(format!("XML 宣言失敗: {}", e)))?;
+
+    let mut rss_start = BytesStart::new("rss");
+    rss_start.push_attribute(("version", "2.0"));
+    rss_start.push_attribute(("xmlns:atom", "http://www.w3.org/2005/Atom"));
+    rss_start.push_attribute(("xmlns:dc", "http://purl.org/dc/elements/1.1/"));
+    writer
+        .write_event(Event::Start(rss_start))
+        .map_err(|e| AppError::feed(format!("rss 開始失敗: {}", e)))?;
+    writer
+        .write_event(Event::Start(BytesStart::new("channel")))
+        .map_err(|e| AppError::feed(format!("channel 開始失敗: {}", e)))?;
+
+    write_text_element(&mut writer, "title", &feed.title)?;
+    write_text_element(&mut writer, "link", &feed.index_url)?;
+    // description は RSS 2.0 の必須要素
+    let description = if feed.description.trim().is_empty() {
+        &feed.title
+    } else {
+        &feed.description
+    };
+    write_text_element(&mut writer, "description", description)?;
+    if let Some(lang) = feed.lang.as_deref().filter(|l| !l.trim().is_empty()) {
+        write_text_element(&mut writer, "language", lang)?;
+    }
+    if let Some(rights) = feed.rights.as_deref().filter(|r| !r.trim().is_empty()) {
+        write_text_element(&mut writer, "copyright", rights)?;
+    }
+
+    let mut self_link = BytesStart::new("atom:link");
+    self_link.push_attribute(("href", self_url));
+    self_link.push_attribute(("rel", "self"));
+    self_link.push_attribute(("type", "application/rss+xml"));
+    writer
+        .write_event(Event::Empty(self_link))
+        .map_err(|e| AppError::feed(format!("atom:link 書き込み失敗: {}", e)))?;
+
+    write_text_element(&mut writer, "lastBuildDate", &feed.updated.to_rfc2822())?;
+    write_text_element(&mut writer, "generator", GENERATOR_NAME)?;
+
+    for entry in &feed.entries {
+        writer
+            .write_event(Event::Start(BytesStart::new("item")))
+            .map_err(|e| AppError::feed(format!("item 開始失敗: {}", e)))?;
+
+        write_text_element(&mut writer, "title", &entry.title)?;
+        write_text_element(&mut writer, "link", &entry.link)?;
+
+        // 記事 URL はそのまま恒久リンクとして使える
+        let mut guid = BytesStart::new("guid");
+        guid.push_attribute(("isPermaLink", "true"));
+        writer
+            .write_event(Event::Start(guid))
+            .map_err(|e| AppError::feed(format!("guid 開始失敗: {}", e)))?;
+        writer
+            .write_event(Event::Text(BytesText::new(&entry.link)))
+            .map_err(|e| AppError::feed(format!("guid 書き込み失敗: {}", e)))?;
+        writer
+            .write_event(Event::End(BytesEnd::new("guid")))
+            .map_err(|e| AppError::feed(format!("guid 終了失敗: {}", e)))?;
+
+        let pub_date = entry.published.unwrap_or(entry.updated);
+        write_text_element(&mut writer, "pubDate", &pub_date.to_rfc2822())?;
+        if let Some(author) = entry.author.as_ref().filter(|a| !a.name.trim().is_empty()) {
+            write_text_element(&mut writer, "dc:creator", &author.name)?;
+        }
+        // エスケープされた HTML として渡す
+        write_text_element(&mut writer, "description", &entry.summary_html)?;
+
+        writer
+            .write_event(Event::End(BytesEnd::new("item")))
+            .map_err(|e| AppError::feed(format!("item 終了失敗: {}", e)))?;
+    }
+
+    writer
+        .write_event(Event::End(BytesEnd::new("channel")))
+        .map_err(|e| AppError::feed(format!("channel 終了失敗: {}", e)))?;
+    writer
+        .write_event(Event::End(BytesEnd::new("rss")))
+        .map_err(|e| AppError::feed(format!("rss 終了失敗: {}", e)))?;
+
+    let output = writer.into_inner().into_inner();
+    let xml =
+        String::from_utf8(output).map_err(|e| AppError::feed(format!("XML 変換失敗: {}", e)))?;
+    Ok(xml)
+}
@@ -0,0 +1,104 @@
+use crate::error::AppError;
+use chrono::DateTime;
+use serde_json::{Map, Value};
+
+/// このバイナリが読み書きする state のスキーマバージョン
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), AppError>;
+
+// MIGRATIONS[n] はバージョン n から n + 1 への移行
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// schema_version が無い state はバージョン 0 とみなす
+pub fn schema_version(value: &Value) -> Result<u32, AppError> {
+    match value.get("schema_version") {
+        None | Some(Value::Null) => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| AppError::state(format!("state の schema_version が不正です: {}", v))),
+    }
+}
+
+/// 古い state を現在のスキーマまで順に移行する。移行前のバージョンを返す
+pub fn migrate_to_current(value: &mut Value) -> Result<u32, AppError> {
+    let from = schema_version(value)?;
+    // 壊れているのではないので、バックアップ (同じく新しいスキーマ) からの復旧は試みない
+    if from > CURRENT_SCHEMA_VERSION {
+        return Err(AppError::feed(format!(
+            "state のスキーマ (v{}) がこのバイナリ (v{}) より新しいため読み込めません。qiita-feed を更新してください",
+            from, CURRENT_SCHEMA_VERSION
+        )));
+    }
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| AppError::state("state のトップレベルがオブジェクトではありません"))?;
+    for version in from..CURRENT_SCHEMA_VERSION {
+        MIGRATIONS[version as usize](object).map_err(|e| {
+            AppError::state(format!(
+                "state のスキーマ移行失敗 (v{} -> v{}): {}",
+                version,
+                version + 1,
+                e
+            ))
+        })?;
+        object.insert("schema_version".to_string(), Value::from(version + 1));
+    }
+    if from < CURRENT_SCHEMA_VERSION {
+        eprintln!(
+            "state のスキーマを移行しました: v{} -> v{}",
+            from, CURRENT_SCHEMA_VERSION
+        );
+    }
+    Ok(from)
+}
+
+// v0: バージョン導入前。後から足した配列フィールドを明示的に埋める
+fn migrate_v0_to_v1(object: &mut Map<String, Value>) -> Result<(), AppError> {
+    let items = object
+        .entry("items")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let items = items
+        .as_array_mut()
+        .ok_or_else(|| AppError::state("items が配列ではありません"))?;
+    for item in items.iter_mut() {
+        let Some(item) = item.as_object_mut() else {
+            continue;
+        };
+        for field in ["tags", "likes_history"] {
+            item.entry(field)
+                .or_insert_with(|| Value::Array(Vec::new()));
+        }
+    }
+    object
+        .entry("watchlist")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+// v2: first_seen / qualified_at を追加。既存 item は移行時に feed 入りしたように見えないよう
+// published で埋め、published が無いか読めない場合だけ last_seen で埋める
+fn migrate_v1_to_v2(object: &mut Map<String, Value>) -> Result<(), AppError> {
+    let Some(items) = object.get_mut("items").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+    for item in items.iter_mut() {
+        let Some(item) = item.as_object_mut() else {
+            continue;
+        };
+        let published = item
+            .get("published")
+            .and_then(Value::as_str)
+            .filter(|value| DateTime::parse_from_rfc3339(value).is_ok())
+            .map(Value::from);
+        let since = published
+            .or_else(|| item.get("last_seen").cloned())
+            .unwrap_or(Value::Null);
+        for field in ["first_seen", "qualified_at"] {
+            item.entry(field).or_insert_with(|| since.clone());
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,27 @@
-use crate::error::AppError;
-use crate::qiita_api::QiitaItem;
+use crate::canonical::{canonicalize_url, extract_item_id};
+use crate::digest::DigestRecord;
+use crate::entry_id::{legacy_entry_id, stable_entry_id, EntryIdMode};
+use crate::error::{AppError, ErrorKind};
+use crate::fingerprint::content_hash;
+use crate::fsio::existing_backups;
+use crate::history::{downsample, record_sample, LikesSample};
+use crate::lock::StateLock;
+use crate::merge::{
+    merge_option, merge_tags, merge_value, FieldChange, FieldPolicy, MergePolicy, MergeReport,
+};
+use crate::milestone::{detect_milestones, merge_milestones, MilestoneEvent};
+use crate::prune::{default_prune_rules, PruneAction, PruneRemoval, PruneReport, PruneRule};
+use crate::qiita_api::{LikesCount, QiitaItem};
+use crate::schema::CURRENT_SCHEMA_VERSION;
+use crate::storage::open_storage;
 use chrono::{DateTime, Duration, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+/// save 時に残す state のバックアップ世代数の既定値
+pub const DEFAULT_STATE_BACKUPS: usize = 3;
+
 // フィードを永続化して再実行時に差分を保持する
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredItem {
@@ -17,44 +33,420 @@ pub struct StoredItem {
     pub published: Option<String>,
     pub updated: Option<String>,
     pub author_name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub likes_count: u32,
+    /// true の場合 likes_count は「N 以上」を表す
+    #[serde(default)]
+    pub likes_lower_bound: bool,
+    /// 最後に正確な likes 数を集計した時刻
+    #[serde(default)]
+    pub likes_counted_at: Option<String>,
+    /// 最後に likes を確認した時刻 (下限値のみの確認も含む)
+    #[serde(default)]
+    pub likes_checked_at: Option<String>,
+    #[serde(default)]
+    pub stocks_count: Option<u32>,
+    /// likes / stocks の推移 (古いものほど粗く間引く)
+    #[serde(default)]
+    pub likes_history: Vec<LikesSample>,
+    /// 最初に観測した時刻 (watchlist での観測を含む)
+    pub first_seen: String,
+    /// min_likes を満たして feed 側に入った時刻
+    pub qualified_at: String,
     pub last_seen: String,
+    /// prune_rules の pinned で常に残す
+    #[serde(default)]
+    pub pinned: bool,
+    /// Qiita 側で削除されたことを確認した時刻 (feed に再び現れたら消す)
+    #[serde(default)]
+    pub removed_at: Option<String>,
+    /// タイトルと summary のハッシュ
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// content_hash の変化 (実際の編集) を検出した時刻
+    #[serde(default)]
+    pub edited_at: Option<String>,
+    /// feed に出す Atom entry の id。一度決めたら変えない
+    #[serde(default)]
+    pub entry_id: Option<String>,
+    /// likes が超えた節目 (一度記録したら再び出さない)
+    #[serde(default)]
+    pub milestones: Vec<MilestoneEvent>,
+}
+
+impl StoredItem {
+    /// feed 入りした時刻。パースできない場合は None
+    pub fn qualified_time(&self) -> Option<DateTime<Utc>> {
+        parse_datetime(&self.qualified_at)
+    }
+
+    pub fn first_seen_time(&self) -> Option<DateTime<Utc>> {
+        parse_datetime(&self.first_seen)
+    }
+
+    /// 最後に確定した likes。今の値が下限値なら likes_history の最新の値
+    pub fn last_exact_likes(&self) -> Option<u32> {
+        if self.likes_lower_bound {
+            self.likes_history.last().map(|sample| sample.likes)
+        } else {
+            Some(self.likes_count)
+        }
+    }
+}
+
+// min_likes 未満の候補を一定期間見守るための記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedItem {
+    pub key: String,
+    pub item_id: Option<String>,
+    pub title: String,
+    pub link: String,
+    pub summary: Option<String>,
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub author_name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub likes_count: u32,
+    #[serde(default)]
+    pub likes_lower_bound: bool,
+    /// likes_count より前に最後に確定した likes。feed 入りしたときの節目の検出に使う
+    #[serde(default)]
+    pub previous_likes: Option<u32>,
+    pub watched_since: String,
+    pub last_checked: String,
+}
+
+impl WatchedItem {
+    fn to_qiita_item(&self) -> QiitaItem {
+        QiitaItem {
+            item_id: self.item_id.clone(),
+            title: self.title.clone(),
+            link: self.link.clone(),
+            summary: self.summary.clone(),
+            published: self.published.clone(),
+            updated: self.updated.clone(),
+            author_name: self.author_name.clone(),
+            tags: self.tags.clone(),
+            likes_count: self.likes_count,
+            likes_lower_bound: self.likes_lower_bound,
+            likes_full_scan: false,
+            stocks_count: None,
+        }
+    }
+
+    fn last_exact_likes(&self) -> Option<u32> {
+        if self.likes_lower_bound {
+            self.previous_likes
+        } else {
+            Some(self.likes_count)
+        }
+    }
+
+    /// 今の likes で上書きする前に、確定している値を previous_likes に移す
+    fn observe_likes(&mut self, count: u32, lower_bound: bool) {
+        self.previous_likes = self.last_exact_likes();
+        self.likes_count = count;
+        self.likes_lower_bound = lower_bound;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct StateFile {
+pub(crate) struct StateFile {
+    #[serde(default)]
+    pub schema_version: u32,
     pub items: Vec<StoredItem>,
+    #[serde(default)]
+    pub watchlist: Vec<WatchedItem>,
+    #[serde(default)]
+    pub digests: Vec<DigestRecord>,
 }
 
 #[derive(Debug, Default)]
 pub struct StateStore {
     pub items: HashMap<String, StoredItem>,
+    pub watchlist: HashMap<String, WatchedItem>,
+    /// 締まった期間の digest (締めた時点の内容)
+    pub digests: Vec<DigestRecord>,
+    /// merge_from_feed で使うフィールド単位の統合ポリシー (保存はしない)
+    pub merge_policy: MergePolicy,
+    /// merge_from_feed で検出する likes の節目 (保存はしない)。空なら検出しない
+    pub milestones: Vec<u32>,
+    /// prune で pinned と同じく扱う item の key (curation の pin など。保存はしない)
+    pub pinned_keys: HashSet<String>,
 }
 
 impl StateStore {
+    /// state 用の排他ロックを取得する。保持している間は他プロセスの load/save を待たせる
+    pub fn lock(path: &Path, timeout: std::time::Duration) -> Result<StateLock, AppError> {
+        StateLock::acquire(path, timeout)
+    }
+
+    /// state を読み込み、URL の表記揺れで重複した item をまとめる
     pub fn load(path: &Path) -> Result<Self, AppError> {
-        if !path.exists() {
-            return Ok(Self::default());
+        let mut store = Self::load_with_fallback(path)?;
+        let merged = store.dedupe_items(Utc::now());
+        if merged > 0 {
+            eprintln!("重複した item をまとめました: {} 件", merged);
         }
-        let content = fs::read_to_string(path)
-            .map_err(|e| AppError::feed(format!("state 読み込み失敗: {}", e)))?;
-        let file: StateFile = serde_json::from_str(&content)
-            .map_err(|e| AppError::feed(format!("state パース失敗: {}", e)))?;
+        Ok(store)
+    }
+
+    /// 拡張子に応じたストレージから state を読み込む。本体が壊れていれば新しいバックアップから復旧する
+    fn load_with_fallback(path: &Path) -> Result<Self, AppError> {
+        let err = match open_storage(path).load() {
+            Ok(store) => return Ok(store),
+            Err(err) if matches!(err.kind, ErrorKind::State) => err,
+            Err(err) => return Err(err),
+        };
+        for backup in existing_backups(path) {
+            match open_storage(&backup).load() {
+                Ok(store) => {
+                    eprintln!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
+                    eprintln!(
+                        "警告: state が壊れているためバックアップから復旧しました: path={} backup={} error={}",
+                        path.display(),
+                        backup.display(),
+                        err
+                    );
+                    eprintln!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
+                    return Ok(store);
+                }
+                Err(backup_err) => {
+                    eprintln!(
+                        "バックアップも読み込めません: backup={} error={}",
+                        backup.display(),
+                        backup_err
+                    );
+                }
+            }
+        }
+        Err(err)
+    }
+
+    pub(crate) fn from_state_file(file: StateFile) -> Self {
         let mut store = StateStore::default();
         for item in file.items {
             store.items.insert(item.key.clone(), item);
         }
-        Ok(store)
+        for item in file.watchlist {
+            store.watchlist.insert(item.key.clone(), item);
+        }
+        store.digests = file.digests;
+        store
     }
 
-    pub fn merge_from_feed(&mut self, items: &[QiitaItem], now: DateTime<Utc>) -> usize {
-        let mut merged = 0;
+    pub(crate) fn to_state_file(&self) -> StateFile {
+        let mut watchlist: Vec<_> = self.watchlist.values().cloned().collect();
+        watchlist.sort_by_key(|item| item.key.clone());
+        StateFile {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            items: self.to_sorted_vec(),
+            watchlist,
+            digests: self.digests.clone(),
+        }
+    }
+
+    /// feed の item を merge_policy に従って統合し、item ごとの変更内容を返す
+    pub fn merge_from_feed(&mut self, items: &[QiitaItem], now: DateTime<Utc>) -> Vec<MergeReport> {
+        self.merge_with_watched(items, &HashMap::new(), now)
+    }
+
+    /// merge_from_feed の本体。watched_likes は watchlist から移る item の以前の likes
+    fn merge_with_watched(
+        &mut self,
+        items: &[QiitaItem],
+        watched_likes: &HashMap<String, u32>,
+        now: DateTime<Utc>,
+    ) -> Vec<MergeReport> {
+        let policy = self.merge_policy.clone();
+        let mut reports = Vec::new();
         for item in items {
             let Some(key) = item_key(item) else {
                 eprintln!("item の識別子が不足しているためスキップ: title={}", item.title);
                 continue;
             };
+            let previous = self.items.get(&key);
+            let mut changes = Vec::new();
+            // 下限値のみの場合は既存の正確な値を下回らないようにする
+            let likes_policy = if item.likes_lower_bound {
+                FieldPolicy::Max
+            } else {
+                policy.likes_count
+            };
+            let likes_count = merge_value(
+                "likes_count",
+                likes_policy,
+                previous.map(|p| &p.likes_count),
+                item.likes_count,
+                &mut changes,
+            );
+            let likes_lower_bound = merged_lower_bound(
+                previous.map(|p| (p.likes_count, p.likes_lower_bound)),
+                item.likes_count,
+                item.likes_lower_bound,
+                likes_count,
+            );
+            let mut milestones = previous.map(|p| p.milestones.clone()).unwrap_or_default();
+            // 新しい item は以前の likes が分からないので節目を超えたとはみなさない。
+            // watchlist から移る item は見守っていた間に確定した likes と比べる
+            let previous_likes = match previous {
+                Some(prev) => prev.last_exact_likes(),
+                None => watched_likes.get(&key).copied().or_else(|| {
+                    self.watchlist
+                        .get(&key)
+                        .and_then(WatchedItem::last_exact_likes)
+                }),
+            };
+            for event in detect_milestones(
+                &self.milestones,
+                previous_likes,
+                likes_count,
+                &milestones,
+                now,
+            ) {
+                changes.push(FieldChange {
+                    field: "milestone",
+                    before: previous
+                        .map(|p| p.likes_count)
+                        .or(previous_likes)
+                        .map(|l| l.to_string()),
+                    after: Some(event.threshold.to_string()),
+                });
+                milestones.push(event);
+            }
+            // max_pages で頭打ちの件数も、数え直しを試みた時刻として記録する
+            let likes_counted_at = if item.likes_lower_bound && !item.likes_full_scan {
+                previous.and_then(|p| p.likes_counted_at.clone())
+            } else {
+                Some(now.to_rfc3339())
+            };
+            let stocks_count = merge_option(
+                "stocks_count",
+                policy.stocks_count,
+                previous.map(|p| &p.stocks_count),
+                item.stocks_count,
+                &mut changes,
+            );
+            let mut likes_history = previous
+                .map(|p| p.likes_history.clone())
+                .unwrap_or_default();
+            if !item.likes_lower_bound {
+                record_sample(&mut likes_history, likes_count, stocks_count, now);
+            }
+            // 初回観測と feed 入りの時刻は引き継ぐ
+            let first_seen = previous
+                .map(|p| p.first_seen.clone())
+                .or_else(|| self.watchlist.get(&key).map(|w| w.watched_since.clone()))
+                .unwrap_or_else(|| now.to_rfc3339());
+            let qualified_at = previous
+                .map(|p| p.qualified_at.clone())
+                .unwrap_or_else(|| now.to_rfc3339());
+            let title = merge_value(
+                "title",
+                policy.title,
+                previous.map(|p| &p.title),
+                item.title.clone(),
+                &mut changes,
+            );
+            let summary = merge_option(
+                "summary",
+                policy.summary,
+                previous.map(|p| &p.summary),
+                item.summary.clone(),
+                &mut changes,
+            );
+            // ハッシュが無い既存 item (導入前の state) は編集扱いにしない
+            let content_hash = content_hash(&title, summary.as_deref());
+            let mut edited_at = previous.and_then(|p| p.edited_at.clone());
+            if previous
+                .and_then(|p| p.content_hash.as_deref())
+                .is_some_and(|prev| prev != content_hash)
+            {
+                changes.push(FieldChange {
+                    field: "edited_at",
+                    before: edited_at.clone(),
+                    after: Some(now.to_rfc3339()),
+                });
+                edited_at = Some(now.to_rfc3339());
+            }
             let stored = StoredItem {
+                key: key.clone(),
+                item_id: item.item_id.clone(),
+                title,
+                link: item.link.clone(),
+                summary,
+                published: merge_option(
+                    "published",
+                    policy.published,
+                    previous.map(|p| &p.published),
+                    item.published.clone(),
+                    &mut changes,
+                ),
+                updated: merge_option(
+                    "updated",
+                    policy.updated,
+                    previous.map(|p| &p.updated),
+                    item.updated.clone(),
+                    &mut changes,
+                ),
+                author_name: merge_option(
+                    "author_name",
+                    policy.author_name,
+                    previous.map(|p| &p.author_name),
+                    item.author_name.clone(),
+                    &mut changes,
+                ),
+                tags: merge_tags(
+                    policy.tags,
+                    previous.map(|p| &p.tags),
+                    item.tags.clone(),
+                    &mut changes,
+                ),
+                likes_count,
+                likes_lower_bound,
+                likes_counted_at,
+                likes_checked_at: Some(now.to_rfc3339()),
+                stocks_count,
+                likes_history,
+                first_seen,
+                qualified_at,
+                last_seen: now.to_rfc3339(),
+                pinned: previous.is_some_and(|p| p.pinned),
+                removed_at: None,
+                content_hash: Some(content_hash),
+                edited_at,
+                entry_id: previous.and_then(|p| p.entry_id.clone()),
+                milestones,
+            };
+            reports.push(MergeReport {
+                key: key.clone(),
+                is_new: previous.is_none(),
+                changes,
+            });
+            self.watchlist.remove(&key);
+            self.items.insert(key, stored);
+        }
+        reports
+    }
+
+    /// min_likes 未満の item を watchlist に登録・更新する
+    pub fn watch_from_feed(&mut self, items: &[QiitaItem], now: DateTime<Utc>) -> usize {
+        let mut watched = 0;
+        for item in items {
+            let Some(key) = item_key(item) else {
+                continue;
+            };
+            if self.items.contains_key(&key) {
+                // 既に feed 入りした item は watchlist に戻さない
+                continue;
+            }
+            let existing = self.watchlist.get(&key);
+            let watched_since = existing
+                .map(|w| w.watched_since.clone())
+                .unwrap_or_else(|| now.to_rfc3339());
+            let mut entry = WatchedItem {
                 key: key.clone(),
                 item_id: item.item_id.clone(),
                 title: item.title.clone(),
@@ -63,34 +455,277 @@ impl StateStore {
                 published: item.published.clone(),
                 updated: item.updated.clone(),
                 author_name: item.author_name.clone(),
+                tags: item.tags.clone(),
                 likes_count: item.likes_count,
-                last_seen: now.to_rfc3339(),
+                likes_lower_bound: item.likes_lower_bound,
+                previous_likes: None,
+                watched_since,
+                last_checked: now.to_rfc3339(),
             };
-            self.items.insert(key, stored);
-            merged += 1;
+            if let Some(existing) = existing {
+                entry.previous_likes = existing.previous_likes;
+                entry.likes_count = existing.likes_count;
+                entry.likes_lower_bound = existing.likes_lower_bound;
+                entry.observe_likes(item.likes_count, item.likes_lower_bound);
+            }
+            self.watchlist.insert(key, entry);
+            watched += 1;
         }
-        merged
+        watched
+    }
+
+    /// watchlist のうち再確認する item を確認が古い順に返す。exclude に含まれる key は除く
+    pub fn watch_candidates(&self, exclude: &HashSet<String>) -> Vec<(String, String)> {
+        let mut list: Vec<&WatchedItem> = self
+            .watchlist
+            .values()
+            .filter(|item| item.item_id.is_some() && !exclude.contains(&item.key))
+            .collect();
+        list.sort_by_key(|item| (parse_datetime(&item.last_checked), item.key.clone()));
+        list.into_iter()
+            .filter_map(|item| {
+                item.item_id
+                    .clone()
+                    .map(|item_id| (item.key.clone(), item_id))
+            })
+            .collect()
+    }
+
+    pub fn update_watch_likes(&mut self, key: &str, likes: LikesCount, now: DateTime<Utc>) -> bool {
+        let Some(item) = self.watchlist.get_mut(key) else {
+            return false;
+        };
+        item.observe_likes(likes.count, likes.lower_bound);
+        item.last_checked = now.to_rfc3339();
+        true
+    }
+
+    /// min_likes に達した watchlist の item を feed 側へ移す
+    pub fn promote_watchlist(&mut self, min_likes: u32, now: DateTime<Utc>) -> usize {
+        let watched: Vec<&WatchedItem> = self
+            .watchlist
+            .values()
+            .filter(|item| item.likes_count >= min_likes)
+            .collect();
+        let promoted: Vec<QiitaItem> = watched.iter().map(|item| item.to_qiita_item()).collect();
+        let watched_likes: HashMap<String, u32> = watched
+            .iter()
+            .filter_map(|item| item.previous_likes.map(|likes| (item.key.clone(), likes)))
+            .collect();
+        self.merge_with_watched(&promoted, &watched_likes, now).len()
+    }
+
+    pub fn prune_watchlist(&mut self, now: DateTime<Utc>, watch_days: u32) {
+        let cutoff = now - Duration::days(watch_days as i64);
+        self.watchlist.retain(|_, item| {
+            parse_datetime(&item.watched_since).is_some_and(|dt| dt >= cutoff)
+        });
+    }
+
+    /// item の likes / stocks 推移を古い順に返す
+    pub fn likes_history(&self, key: &str) -> Option<&[LikesSample]> {
+        self.items.get(key).map(|item| item.likes_history.as_slice())
+    }
+
+    /// 正確な likes 再集計が必要かどうか。未保存の item はしきい値判定のみで足りる
+    pub fn exact_recount_due(&self, key: &str, now: DateTime<Utc>, interval_hours: u32) -> bool {
+        let Some(item) = self.items.get(key) else {
+            return false;
+        };
+        match item.likes_counted_at.as_deref().and_then(parse_datetime) {
+            Some(counted_at) => now - counted_at >= Duration::hours(interval_hours as i64),
+            None => true,
+        }
+    }
+
+    /// likes 再集計の候補を確認が古い順に返す。exclude に含まれる key と item_id の無い item は除く
+    pub fn refresh_candidates(&self, exclude: &HashSet<String>) -> Vec<(String, String)> {
+        let mut list: Vec<&StoredItem> = self
+            .items
+            .values()
+            .filter(|item| {
+                item.item_id.is_some()
+                    && item.removed_at.is_none()
+                    && !exclude.contains(&item.key)
+            })
+            .collect();
+        list.sort_by_key(|item| {
+            (
+                item.likes_checked_at.as_deref().and_then(parse_datetime),
+                item.key.clone(),
+            )
+        });
+        list.into_iter()
+            .filter_map(|item| {
+                item.item_id
+                    .clone()
+                    .map(|item_id| (item.key.clone(), item_id))
+            })
+            .collect()
+    }
+
+    /// 再集計した likes を反映する。last_seen は更新しない
+    pub fn update_likes(&mut self, key: &str, likes: LikesCount, now: DateTime<Utc>) -> bool {
+        let Some(item) = self.items.get_mut(key) else {
+            return false;
+        };
+        // merge_from_feed と同じく、下限値は既存の値を下回らないようにする
+        let likes_policy = if likes.lower_bound {
+            FieldPolicy::Max
+        } else {
+            self.merge_policy.likes_count
+        };
+        let likes_count = merge_value(
+            "likes_count",
+            likes_policy,
+            Some(&item.likes_count),
+            likes.count,
+            &mut Vec::new(),
+        );
+        // feed から外れた後に超えた節目もここで記録する
+        let reached = detect_milestones(
+            &self.milestones,
+            item.last_exact_likes(),
+            likes_count,
+            &item.milestones,
+            now,
+        );
+        item.milestones.extend(reached);
+        item.likes_lower_bound = merged_lower_bound(
+            Some((item.likes_count, item.likes_lower_bound)),
+            likes.count,
+            likes.lower_bound,
+            likes_count,
+        );
+        item.likes_count = likes_count;
+        if likes.lower_bound {
+            if likes.full_scan {
+                item.likes_counted_at = Some(now.to_rfc3339());
+            }
+        } else {
+            item.likes_counted_at = Some(now.to_rfc3339());
+            record_sample(&mut item.likes_history, likes.count, item.stocks_count, now);
+        }
+        item.likes_checked_at = Some(now.to_rfc3339());
+        true
     }
 
-    pub fn prune(&mut self, now: DateTime<Utc>, max_days: u32, max_items: usize) {
-        let cutoff = now - Duration::days(max_days as i64);
-        self.items.retain(|_, item| {
-            if let Some(dt) = parse_datetime(&item.last_seen) {
-                dt >= cutoff
+    /// Qiita 側で削除された item に印を付ける。既に付いていれば最初の時刻を残す
+    pub fn mark_removed(&mut self, key: &str, now: DateTime<Utc>) -> bool {
+        let Some(item) = self.items.get_mut(key) else {
+            return false;
+        };
+        if item.removed_at.is_none() {
+            item.removed_at = Some(now.to_rfc3339());
+        }
+        true
+    }
+
+    pub fn set_pinned(&mut self, key: &str, pinned: bool) -> bool {
+        let Some(item) = self.items.get_mut(key) else {
+            return false;
+        };
+        item.pinned = pinned;
+        true
+    }
+
+    /// entry_id が未記録の item に id を割り当てる。割り当てた件数を返す。
+    /// legacy では今回より前から state にある item に旧形式の id を固定する
+    pub fn assign_entry_ids(&mut self, mode: EntryIdMode, now: DateTime<Utc>) -> usize {
+        let mut assigned = 0;
+        for item in self.items.values_mut().filter(|i| i.entry_id.is_none()) {
+            let delivered_before = item.qualified_time().is_some_and(|t| t < now);
+            let id = match mode {
+                EntryIdMode::Legacy if delivered_before => legacy_entry_id(item, now),
+                _ => stable_entry_id(item),
+            };
+            item.entry_id = Some(id);
+            assigned += 1;
+        }
+        assigned
+    }
+
+    /// last_seen が max_days より古い item を消し、max_items を超えた分を last_seen の古い順に消す
+    pub fn prune(&mut self, now: DateTime<Utc>, max_days: u32, max_items: usize) -> PruneReport {
+        self.prune_with_rules(&default_prune_rules(max_days), max_items, now)
+    }
+
+    /// ルールに従って item を消し、消した item とルールを返す
+    pub fn prune_with_rules(
+        &mut self,
+        rules: &[PruneRule],
+        max_items: usize,
+        now: DateTime<Utc>,
+    ) -> PruneReport {
+        let report = self.plan_prune(rules, max_items, now);
+        for removal in &report.removed {
+            self.items.remove(&removal.key);
+        }
+        report
+    }
+
+    /// 削除せずに、ルールごとに何が消えるかだけを返す
+    pub fn plan_prune(
+        &self,
+        rules: &[PruneRule],
+        max_items: usize,
+        now: DateTime<Utc>,
+    ) -> PruneReport {
+        let mut report = PruneReport::default();
+        let mut kept = 0;
+        // 件数上限で消してよいのは、どのルールも判定しなかった item だけ
+        let mut candidates: Vec<&StoredItem> = Vec::new();
+        for item in self.to_sorted_refs() {
+            let decide = |item: &StoredItem| {
+                rules
+                    .iter()
+                    .find_map(|rule| rule.evaluate(item, now).map(|action| (rule, action)))
+            };
+            // curation の pin は state の pinned と同じく扱う
+            let pinned = item.pinned || self.pinned_keys.contains(&item.key);
+            let decided = if pinned && !item.pinned {
+                decide(&StoredItem {
+                    pinned: true,
+                    ..item.clone()
+                })
             } else {
-                false
+                decide(item)
+            };
+            match decided {
+                Some((rule, PruneAction::Remove)) => report.removed.push(PruneRemoval {
+                    key: item.key.clone(),
+                    title: item.title.clone(),
+                    rule: rule.to_string(),
+                }),
+                Some((_, PruneAction::Keep)) => kept += 1,
+                None => {
+                    kept += 1;
+                    // pinned は件数上限でも消さない
+                    if !pinned {
+                        candidates.push(item);
+                    }
+                }
             }
-        });
+        }
 
-        if self.items.len() > max_items {
-            let mut list: Vec<_> = self.items.values().cloned().collect();
-            list.sort_by_key(|item| parse_datetime(&item.last_seen).unwrap_or_else(|| now));
-            let keep = list.split_off(list.len().saturating_sub(max_items));
-            self.items = keep
-                .into_iter()
-                .map(|item| (item.key.clone(), item))
-                .collect();
+        if kept > max_items {
+            candidates.sort_by_key(|item| parse_datetime(&item.last_seen).unwrap_or(now));
+            let excess = kept - max_items;
+            for item in candidates.into_iter().take(excess) {
+                report.removed.push(PruneRemoval {
+                    key: item.key.clone(),
+                    title: item.title.clone(),
+                    rule: format!("max_items({})", max_items),
+                });
+            }
         }
+        report
+    }
+
+    fn to_sorted_refs(&self) -> Vec<&StoredItem> {
+        let mut list: Vec<&StoredItem> = self.items.values().collect();
+        list.sort_by(|a, b| a.key.cmp(&b.key));
+        list
     }
 
     pub fn to_sorted_vec(&self) -> Vec<StoredItem> {
@@ -99,30 +734,226 @@ impl StateStore {
         list
     }
 
-    pub fn save(&self, path: &Path) -> Result<(), AppError> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| AppError::feed(format!("state ディレクトリ作成失敗: {}", e)))?;
+    /// key・item_id・link のいずれかから保存済み item の key を探す
+    pub fn find_key(&self, id: &str) -> Option<String> {
+        if self.items.contains_key(id) {
+            return Some(id.to_string());
         }
-        let file = StateFile {
-            items: self.to_sorted_vec(),
+        let canonical = canonicalize_url(id);
+        self.items
+            .values()
+            .find(|item| {
+                item.item_id.as_deref() == Some(id) || canonicalize_url(&item.link) == canonical
+            })
+            .map(|item| item.key.clone())
+    }
+
+    /// key を正規形 (item_id か正規化した URL) にそろえ、同じ記事の item を 1 件にまとめる。
+    /// まとめた件数を返す
+    pub fn dedupe_items(&mut self, now: DateTime<Utc>) -> usize {
+        let mut merged = 0;
+        let mut items: Vec<StoredItem> = std::mem::take(&mut self.items).into_values().collect();
+        items.sort_by(|a, b| a.key.cmp(&b.key));
+        for mut item in items {
+            if item.item_id.is_none() {
+                item.item_id = extract_item_id(&item.link);
+            }
+            let key = canonical_key(item.item_id.as_deref(), &item.link);
+            item.key = key.clone();
+            let item = match self.items.remove(&key) {
+                Some(existing) => {
+                    merged += 1;
+                    // 最後に観測した方のタイトル・リンクを残し、他は豊富な方を採る
+                    let (primary, other) =
+                        if parse_datetime(&item.last_seen) > parse_datetime(&existing.last_seen) {
+                            (item, existing)
+                        } else {
+                            (existing, item)
+                        };
+                    let mut combined = combine_items(primary, other, now);
+                    combined.key = key.clone();
+                    combined
+                }
+                None => item,
+            };
+            self.items.insert(key, item);
+        }
+
+        let mut watchlist: Vec<WatchedItem> =
+            std::mem::take(&mut self.watchlist).into_values().collect();
+        watchlist.sort_by(|a, b| a.key.cmp(&b.key));
+        for mut item in watchlist {
+            if item.item_id.is_none() {
+                item.item_id = extract_item_id(&item.link);
+            }
+            let key = canonical_key(item.item_id.as_deref(), &item.link);
+            item.key = key.clone();
+            if self.items.contains_key(&key) {
+                merged += 1;
+                continue;
+            }
+            // 見守り開始の早い方を残す
+            if let Some(existing) = self.watchlist.get(&key) {
+                merged += 1;
+                if parse_datetime(&existing.watched_since) <= parse_datetime(&item.watched_since) {
+                    continue;
+                }
+            }
+            self.watchlist.insert(key, item);
+        }
+        merged
+    }
+
+    pub fn remove_item(&mut self, key: &str) -> Option<StoredItem> {
+        self.watchlist.remove(key);
+        self.items.remove(key)
+    }
+
+    /// remove の item を keep に統合して remove を削除する
+    pub fn merge_items(
+        &mut self,
+        keep: &str,
+        remove: &str,
+        now: DateTime<Utc>,
+    ) -> Result<&StoredItem, AppError> {
+        if keep == remove {
+            return Err(AppError::config("同じ item 同士は統合できません"));
+        }
+        let other = self
+            .items
+            .remove(remove)
+            .ok_or_else(|| AppError::config(format!("item が見つかりません: {}", remove)))?;
+        let Some(primary) = self.items.remove(keep) else {
+            self.items.insert(remove.to_string(), other);
+            return Err(AppError::config(format!("item が見つかりません: {}", keep)));
         };
-        let json = serde_json::to_string_pretty(&file)
-            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
-        fs::write(path, json)
-            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
-        Ok(())
+        self.watchlist.remove(remove);
+        let merged = combine_items(primary, other, now);
+        let key = merged.key.clone();
+        self.items.insert(key.clone(), merged);
+        Ok(&self.items[&key])
+    }
+
+    /// 拡張子に応じたストレージへ state を書き込む
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        self.save_with_backups(path, DEFAULT_STATE_BACKUPS)
+    }
+
+    /// 既存の state を backups 世代までローテーションしてから書き込む
+    pub fn save_with_backups(&self, path: &Path, backups: usize) -> Result<(), AppError> {
+        let storage = open_storage(path);
+        storage.rotate_backups(backups)?;
+        storage.save(self)
+    }
+}
+
+/// 同じ記事を指す 2 件を統合する。primary の値を優先し、欠けている値は other で補う
+pub(crate) fn combine_items(
+    primary: StoredItem,
+    other: StoredItem,
+    now: DateTime<Utc>,
+) -> StoredItem {
+    let (likes_count, likes_lower_bound) = if other.likes_count > primary.likes_count {
+        (other.likes_count, other.likes_lower_bound)
+    } else {
+        (primary.likes_count, primary.likes_lower_bound)
+    };
+    let mut tags = primary.tags.clone();
+    for tag in &other.tags {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    let mut likes_history = primary.likes_history.clone();
+    likes_history.extend(other.likes_history.iter().cloned());
+    downsample(&mut likes_history, now);
+
+    StoredItem {
+        key: primary.key,
+        item_id: primary.item_id.or(other.item_id),
+        title: primary.title,
+        link: primary.link,
+        summary: primary.summary.or(other.summary),
+        published: primary.published.or(other.published),
+        updated: primary.updated.or(other.updated),
+        author_name: primary.author_name.or(other.author_name),
+        tags,
+        likes_count,
+        likes_lower_bound,
+        likes_counted_at: later_option(primary.likes_counted_at, other.likes_counted_at),
+        likes_checked_at: later_option(primary.likes_checked_at, other.likes_checked_at),
+        stocks_count: primary.stocks_count.max(other.stocks_count),
+        likes_history,
+        first_seen: earlier(primary.first_seen, other.first_seen),
+        qualified_at: earlier(primary.qualified_at, other.qualified_at),
+        last_seen: later(primary.last_seen, other.last_seen),
+        pinned: primary.pinned || other.pinned,
+        // 片方でも残っていれば削除済みとはみなさない
+        removed_at: match (primary.removed_at, other.removed_at) {
+            (Some(a), Some(b)) => Some(later(a, b)),
+            _ => None,
+        },
+        content_hash: primary.content_hash.or(other.content_hash),
+        edited_at: later_option(primary.edited_at, other.edited_at),
+        entry_id: primary.entry_id.or(other.entry_id),
+        milestones: merge_milestones(primary.milestones, other.milestones),
+    }
+}
+
+/// 統合後の likes が下限値かどうか。下限値は既存の値を上回ったときだけ採用し、
+/// 採用しなかった場合は既存の値 (previous は likes と下限値かどうか) の種類を引き継ぐ
+fn merged_lower_bound(
+    previous: Option<(u32, bool)>,
+    incoming: u32,
+    incoming_lower_bound: bool,
+    merged: u32,
+) -> bool {
+    match previous {
+        Some((prev_likes, prev_lower_bound))
+            if merged != incoming || (incoming_lower_bound && incoming <= prev_likes) =>
+        {
+            prev_lower_bound
+        }
+        _ => incoming_lower_bound,
+    }
+}
+
+fn earlier(a: String, b: String) -> String {
+    match (parse_datetime(&a), parse_datetime(&b)) {
+        (Some(x), Some(y)) if y < x => b,
+        (None, Some(_)) => b,
+        _ => a,
+    }
+}
+
+fn later(a: String, b: String) -> String {
+    match (parse_datetime(&a), parse_datetime(&b)) {
+        (Some(x), Some(y)) if y > x => b,
+        (None, Some(_)) => b,
+        _ => a,
+    }
+}
+
+fn later_option(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(later(a, b)),
+        (a, b) => a.or(b),
     }
 }
 
 pub fn item_key(item: &QiitaItem) -> Option<String> {
-    if let Some(id) = &item.item_id {
-        return Some(id.clone());
+    Some(canonical_key(item.item_id.as_deref(), &item.link))
+}
+
+/// item_id があればそれを、無ければ正規化した URL を key にする
+fn canonical_key(item_id: Option<&str>, link: &str) -> String {
+    match item_id {
+        Some(id) => id.to_string(),
+        None => canonicalize_url(link),
     }
-    Some(item.link.clone())
 }
 
-fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+pub(crate) fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(value)
         .ok()
         .map(|dt| dt.with_timezone(&Utc))
@@ -0,0 +1,490 @@
+use crate::compress::{
+    is_gzip_path, read_to_string_maybe_gzip, strip_gzip_extension, with_added_extension,
+    write_atomic_maybe_gzip,
+};
+use crate::error::AppError;
+use crate::fsio::{rotate_backup_group, rotate_backups};
+use crate::schema::migrate_to_current;
+use crate::state::{StateFile, StateStore, StoredItem, WatchedItem};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SQLITE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// JSON Lines 形式で本体から分けて保存する、実行ごとに変わりやすいフィールド
+const VOLATILE_FIELDS: [&str; 7] = [
+    "likes_count",
+    "likes_lower_bound",
+    "likes_counted_at",
+    "likes_checked_at",
+    "stocks_count",
+    "likes_history",
+    "last_seen",
+];
+
+const SQLITE_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS items (
+    key TEXT PRIMARY KEY,
+    item_id TEXT,
+    title TEXT NOT NULL,
+    link TEXT NOT NULL,
+    published_utc TEXT,
+    updated_utc TEXT,
+    likes_count INTEGER NOT NULL,
+    last_seen_utc TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_items_likes ON items (likes_count DESC);
+CREATE INDEX IF NOT EXISTS idx_items_published ON items (published_utc);
+CREATE INDEX IF NOT EXISTS idx_items_updated ON items (updated_utc);
+CREATE INDEX IF NOT EXISTS idx_items_last_seen ON items (last_seen_utc);
+CREATE TABLE IF NOT EXISTS item_tags (
+    key TEXT NOT NULL,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (key, tag)
+);
+CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags (tag);
+CREATE TABLE IF NOT EXISTS watchlist (
+    key TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS digests (
+    feed TEXT NOT NULL,
+    label TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (feed, label)
+);
+"#;
+
+// state の保存先を差し替えるための抽象
+pub trait StateStorage {
+    fn path(&self) -> &Path;
+    fn load(&self) -> Result<StateStore, AppError>;
+    fn save(&self, store: &StateStore) -> Result<(), AppError>;
+
+    /// 保存前に既存の state を keep 世代までバックアップする
+    fn rotate_backups(&self, keep: usize) -> Result<(), AppError> {
+        rotate_backups(self.path(), keep)
+            .map_err(|e| AppError::feed(format!("state バックアップ失敗: {}", e)))
+    }
+}
+
+/// 拡張子が .db / .sqlite / .sqlite3 なら SQLite、.jsonl なら JSON Lines、それ以外は JSON を使う。
+/// 末尾の .gz は形式の判定では無視する
+pub fn open_storage(path: &Path) -> Box<dyn StateStorage> {
+    let inner = strip_gzip_extension(path);
+    if is_sqlite_path(&inner) {
+        Box::new(SqliteStorage::new(path))
+    } else if is_jsonl_path(&inner) {
+        Box::new(JsonlStorage::new(path))
+    } else {
+        Box::new(JsonStorage::new(path))
+    }
+}
+
+pub fn is_jsonl_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+}
+
+pub fn is_sqlite_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SQLITE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// from の state を to の形式へ変換して書き出す
+pub fn migrate_state(from: &Path, to: &Path) -> Result<usize, AppError> {
+    if !from.exists() {
+        return Err(AppError::feed(format!(
+            "移行元の state がありません: {}",
+            from.display()
+        )));
+    }
+    let store = open_storage(from).load()?;
+    open_storage(to).save(&store)?;
+    Ok(store.items.len())
+}
+
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl StateStorage for JsonStorage {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<StateStore, AppError> {
+        if !self.path.exists() {
+            return Ok(StateStore::default());
+        }
+        let content = read_to_string_maybe_gzip(&self.path)
+            .map_err(|e| AppError::state(format!("state 読み込み失敗: {}", e)))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| AppError::state(format!("state パース失敗: {}", e)))?;
+        Ok(StateStore::from_state_file(parse_state_value(value)?))
+    }
+
+    fn save(&self, store: &StateStore) -> Result<(), AppError> {
+        let json = serde_json::to_string_pretty(&store.to_state_file())
+            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
+        write_atomic_maybe_gzip(&self.path, json.as_bytes())
+            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// 1 行 1 item の JSON Lines。頻繁に変わるフィールドは別ファイル (*.volatile.json) に分ける
+pub struct JsonlStorage {
+    path: PathBuf,
+}
+
+impl JsonlStorage {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// articles.jsonl -> articles.volatile.json (articles.jsonl.gz -> articles.volatile.json.gz)
+    pub fn volatile_path(path: &Path) -> PathBuf {
+        if is_gzip_path(path) {
+            let inner = strip_gzip_extension(path).with_extension("volatile.json");
+            return with_added_extension(&inner, "gz");
+        }
+        path.with_extension("volatile.json")
+    }
+}
+
+impl StateStorage for JsonlStorage {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<StateStore, AppError> {
+        if !self.path.exists() {
+            return Ok(StateStore::default());
+        }
+        let content = read_to_string_maybe_gzip(&self.path)
+            .map_err(|e| AppError::state(format!("state 読み込み失敗: {}", e)))?;
+        let volatile_path = Self::volatile_path(&self.path);
+        let volatile_content = read_to_string_maybe_gzip(&volatile_path).map_err(|e| {
+            AppError::state(format!(
+                "state 読み込み失敗: path={} error={}",
+                volatile_path.display(),
+                e
+            ))
+        })?;
+        let mut volatile: Map<String, Value> = serde_json::from_str(&volatile_content)
+            .map_err(|e| AppError::state(format!("state パース失敗: {}", e)))?;
+        let mut volatile_items = match volatile.remove("items") {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        let mut items = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut item: Map<String, Value> = serde_json::from_str(line).map_err(|e| {
+                AppError::state(format!("state パース失敗: line={} error={}", index + 1, e))
+            })?;
+            let key = item.get("key").and_then(Value::as_str).unwrap_or_default();
+            if let Some(Value::Object(fields)) = volatile_items.remove(key) {
+                item.extend(fields);
+            }
+            items.push(Value::Object(item));
+        }
+        volatile.insert("items".to_string(), Value::Array(items));
+        Ok(StateStore::from_state_file(parse_state_value(
+            Value::Object(volatile),
+        )?))
+    }
+
+    fn save(&self, store: &StateStore) -> Result<(), AppError> {
+        let file = store.to_state_file();
+        let mut lines = String::new();
+        let mut volatile_items = Map::new();
+        // to_state_file の items は key 順に並んでいる
+        for item in &file.items {
+            let Value::Object(mut stable) = serde_json::to_value(item)
+                .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?
+            else {
+                continue;
+            };
+            let mut volatile = Map::new();
+            for field in VOLATILE_FIELDS {
+                if let Some(value) = stable.remove(field) {
+                    volatile.insert(field.to_string(), value);
+                }
+            }
+            volatile_items.insert(item.key.clone(), Value::Object(volatile));
+            lines.push_str(&to_json(&stable)?);
+            lines.push('\n');
+        }
+        let volatile = json!({
+            "schema_version": file.schema_version,
+            "items": volatile_items,
+            "watchlist": file.watchlist,
+            "digests": file.digests,
+        });
+        let volatile_json = serde_json::to_string_pretty(&volatile)
+            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
+        write_atomic_maybe_gzip(&Self::volatile_path(&self.path), volatile_json.as_bytes())
+            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
+        write_atomic_maybe_gzip(&self.path, lines.as_bytes())
+            .map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))?;
+        Ok(())
+    }
+
+    fn rotate_backups(&self, keep: usize) -> Result<(), AppError> {
+        rotate_backup_group(&self.path, keep, |p| {
+            vec![p.to_path_buf(), Self::volatile_path(p)]
+        })
+        .map_err(|e| AppError::feed(format!("state バックアップ失敗: {}", e)))
+    }
+}
+
+pub struct SqliteStorage {
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn connect(&self) -> Result<Connection, AppError> {
+        if is_gzip_path(&self.path) {
+            return Err(AppError::config(format!(
+                "SQLite の state は gzip 圧縮に対応していません: {}",
+                self.path.display()
+            )));
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::feed(format!("state ディレクトリ作成失敗: {}", e)))?;
+        }
+        let conn = Connection::open(&self.path)
+            .map_err(|e| AppError::state(format!("SQLite オープン失敗: {}", e)))?;
+        conn.execute_batch(SQLITE_SCHEMA)
+            .map_err(|e| AppError::state(format!("SQLite スキーマ作成失敗: {}", e)))?;
+        Ok(conn)
+    }
+
+    /// likes 数が min_likes 以上の item を likes 降順で返す
+    pub fn items_by_min_likes(&self, min_likes: u32) -> Result<Vec<StoredItem>, AppError> {
+        self.query_items(
+            "SELECT data FROM items WHERE likes_count >= ?1 ORDER BY likes_count DESC, key",
+            params![min_likes],
+        )
+    }
+
+    /// 公開日時が [from, to) に入る item を公開日時順で返す
+    pub fn items_published_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<StoredItem>, AppError> {
+        self.query_items(
+            "SELECT data FROM items WHERE published_utc >= ?1 AND published_utc < ?2 ORDER BY published_utc, key",
+            params![format_utc(from), format_utc(to)],
+        )
+    }
+
+    /// 指定タグ (大文字小文字を区別しない) を持つ item を likes 降順で返す
+    pub fn items_by_tag(&self, tag: &str) -> Result<Vec<StoredItem>, AppError> {
+        self.query_items(
+            "SELECT items.data FROM items JOIN item_tags ON items.key = item_tags.key WHERE item_tags.tag = ?1 ORDER BY items.likes_count DESC, items.key",
+            params![tag],
+        )
+    }
+
+    pub fn item(&self, key: &str) -> Result<Option<StoredItem>, AppError> {
+        let conn = self.connect()?;
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM items WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::feed(format!("SQLite 読み込み失敗: {}", e)))?;
+        data.map(|d| parse_json::<StoredItem>(&d)).transpose()
+    }
+
+    fn query_items(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<StoredItem>, AppError> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare(sql)
+            .map_err(|e| AppError::feed(format!("SQLite クエリ準備失敗: {}", e)))?;
+        let rows = stmt
+            .query_map(params, |row| row.get::<_, String>(0))
+            .map_err(|e| AppError::feed(format!("SQLite 読み込み失敗: {}", e)))?;
+        let mut items = Vec::new();
+        for row in rows {
+            let data = row.map_err(|e| AppError::feed(format!("SQLite 読み込み失敗: {}", e)))?;
+            items.push(parse_json(&data)?);
+        }
+        Ok(items)
+    }
+}
+
+impl StateStorage for SqliteStorage {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<StateStore, AppError> {
+        if !self.path.exists() {
+            return Ok(StateStore::default());
+        }
+        let conn = self.connect()?;
+        let version: u32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| AppError::state(format!("state 読み込み失敗: {}", e)))?;
+        let items: Vec<Value> = read_json_column(&conn, "SELECT data FROM items ORDER BY key")?;
+        let watchlist: Vec<Value> =
+            read_json_column(&conn, "SELECT data FROM watchlist ORDER BY key")?;
+        let digests: Vec<Value> =
+            read_json_column(&conn, "SELECT data FROM digests ORDER BY feed, label")?;
+        // JSON 版と同じ移行処理を通すため 1 つのドキュメントに組み立てる
+        let value = json!({
+            "schema_version": version,
+            "items": items,
+            "watchlist": watchlist,
+            "digests": digests,
+        });
+        Ok(StateStore::from_state_file(parse_state_value(value)?))
+    }
+
+    fn save(&self, store: &StateStore) -> Result<(), AppError> {
+        let mut conn = self.connect()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::feed(format!("SQLite トランザクション開始失敗: {}", e)))?;
+        let file = store.to_state_file();
+        {
+            let write_err =
+                |e: rusqlite::Error| AppError::feed(format!("SQLite 書き込み失敗: {}", e));
+            // 毎回全件を入れ替えて JSON 版と同じ内容にそろえる
+            tx.execute_batch(
+                "DELETE FROM items; DELETE FROM item_tags; DELETE FROM watchlist; DELETE FROM digests;",
+            )
+            .map_err(write_err)?;
+            tx.pragma_update(None, "user_version", file.schema_version)
+                .map_err(write_err)?;
+            let mut insert_item = tx
+                .prepare(
+                    "INSERT INTO items (key, item_id, title, link, published_utc, updated_utc, likes_count, last_seen_utc, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )
+                .map_err(write_err)?;
+            let mut insert_tag = tx
+                .prepare("INSERT OR IGNORE INTO item_tags (key, tag) VALUES (?1, ?2)")
+                .map_err(write_err)?;
+            for item in &file.items {
+                insert_item
+                    .execute(params![
+                        item.key,
+                        item.item_id,
+                        item.title,
+                        item.link,
+                        item.published.as_deref().and_then(normalize_utc),
+                        item.updated.as_deref().and_then(normalize_utc),
+                        item.likes_count,
+                        normalize_utc(&item.last_seen),
+                        to_json(item)?,
+                    ])
+                    .map_err(write_err)?;
+                for tag in &item.tags {
+                    insert_tag
+                        .execute(params![item.key, tag])
+                        .map_err(write_err)?;
+                }
+            }
+            let mut insert_watch = tx
+                .prepare("INSERT INTO watchlist (key, data) VALUES (?1, ?2)")
+                .map_err(write_err)?;
+            for item in &file.watchlist {
+                insert_watch
+                    .execute(params![item.key, to_json::<WatchedItem>(item)?])
+                    .map_err(write_err)?;
+            }
+            let mut insert_digest = tx
+                .prepare("INSERT INTO digests (feed, label, data) VALUES (?1, ?2, ?3)")
+                .map_err(write_err)?;
+            for record in &file.digests {
+                insert_digest
+                    .execute(params![record.feed, record.label, to_json(record)?])
+                    .map_err(write_err)?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| AppError::feed(format!("SQLite コミット失敗: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// スキーマを現在のバージョンまで移行してから StateFile に変換する
+fn parse_state_value(mut value: Value) -> Result<StateFile, AppError> {
+    migrate_to_current(&mut value)?;
+    serde_json::from_value(value).map_err(|e| AppError::state(format!("state パース失敗: {}", e)))
+}
+
+fn read_json_column<T: serde::de::DeserializeOwned>(
+    conn: &Connection,
+    sql: &str,
+) -> Result<Vec<T>, AppError> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| AppError::state(format!("SQLite クエリ準備失敗: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| AppError::state(format!("state 読み込み失敗: {}", e)))?;
+    let mut values = Vec::new();
+    for row in rows {
+        let data = row.map_err(|e| AppError::state(format!("state 読み込み失敗: {}", e)))?;
+        values.push(parse_json(&data)?);
+    }
+    Ok(values)
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(data: &str) -> Result<T, AppError> {
+    serde_json::from_str(data).map_err(|e| AppError::state(format!("state パース失敗: {}", e)))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, AppError> {
+    serde_json::to_string(value).map_err(|e| AppError::feed(format!("state 書き込み失敗: {}", e)))
+}
+
+// タイムゾーン混在でも文字列比較で並ぶよう UTC にそろえる
+fn normalize_utc(value: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| format_utc(dt.with_timezone(&Utc)))
+}
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
@@ -0,0 +1,92 @@
+use crate::curation::CuratedItem;
+use crate::error::AppError;
+use crate::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use serde::{Deserialize, Serialize};
+
+/// path 未指定時のタグ別 feed の置き場所 (サイト内のパス)
+pub const DEFAULT_TAG_FEED_DIR: &str = "feeds/tags";
+
+// 特定のタグの記事だけを集めた追加の feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagFeed {
+    /// Qiita のタグ名 (大文字小文字は区別しない)
+    pub tag: String,
+    /// path 未指定なら feeds/tags/<tag>.xml、title 未指定なら「<site_title> #<tag>」
+    #[serde(flatten)]
+    pub options: ExtraFeedOptions,
+}
+
+impl ExtraFeed for TagFeed {
+    fn kind(&self) -> &'static str {
+        "tag-feed"
+    }
+
+    fn name(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn id_name(&self) -> String {
+        format!("qiita-feed:tags:{}", tag_slug(&self.tag))
+    }
+
+    fn options(&self) -> &ExtraFeedOptions {
+        &self.options
+    }
+
+    fn default_path(&self) -> String {
+        format!("{}/{}.xml", DEFAULT_TAG_FEED_DIR, tag_slug(&self.tag))
+    }
+
+    fn default_title(&self, site_title: &str) -> String {
+        format!("{} #{}", site_title, self.tag.trim())
+    }
+}
+
+impl TagFeed {
+    pub fn matches(&self, item: &CuratedItem) -> bool {
+        let tag = self.tag.trim();
+        item.item
+            .tags
+            .iter()
+            .any(|t| t.trim().eq_ignore_ascii_case(tag))
+    }
+
+    /// 並べ替え済みの item からタグに合うものを順に最大 max_entries 件選ぶ
+    pub fn select(&self, items: &[CuratedItem], default_max: usize) -> Vec<CuratedItem> {
+        items
+            .iter()
+            .filter(|item| self.matches(item))
+            .take(self.options.max_entries(default_max))
+            .cloned()
+            .collect()
+    }
+}
+
+/// タグ名をファイル名に使える形にする (小文字にし、英数字以外は - にする)
+pub fn tag_slug(tag: &str) -> String {
+    let slug: String = tag
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '.' {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.trim_matches(['-', '.']).to_string()
+}
+
+pub fn validate_tag_feeds(feeds: &[TagFeed]) -> Result<(), AppError> {
+    for feed in feeds {
+        if tag_slug(&feed.tag).is_empty() && feed.options.path.is_none() {
+            return Err(AppError::config(format!(
+                "tag_feeds の tag が空かファイル名にできません。path を指定してください: {:?}",
+                feed.tag
+            )));
+        }
+        feed.options.validate("tag_feeds", &feed.tag)?;
+    }
+    Ok(())
+}
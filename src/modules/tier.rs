@@ -0,0 +1,83 @@
+use crate::curation::CuratedItem;
+use crate::error::AppError;
+use crate::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// min_likes を変えた追加の feed (feed-100.xml など)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedTier {
+    /// index.html や dry-run の表示に使う名前
+    pub name: String,
+    /// config の min_likes 以上で指定する
+    pub min_likes: u32,
+    /// path 未指定なら feed-<min_likes>.xml、title 未指定なら「<site_title> (<min_likes>+ likes)」
+    #[serde(flatten)]
+    pub options: ExtraFeedOptions,
+}
+
+impl ExtraFeed for FeedTier {
+    fn kind(&self) -> &'static str {
+        "tier-feed"
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id_name(&self) -> String {
+        format!(
+            "qiita-feed:tiers:{}",
+            self.output_path().trim_start_matches('/')
+        )
+    }
+
+    fn options(&self) -> &ExtraFeedOptions {
+        &self.options
+    }
+
+    fn default_path(&self) -> String {
+        format!("feed-{}.xml", self.min_likes)
+    }
+
+    fn default_title(&self, site_title: &str) -> String {
+        format!("{} ({}+ likes)", site_title, self.min_likes)
+    }
+}
+
+impl FeedTier {
+    /// 並べ替え済みの item から min_likes 以上 (pin した item は常に含む) を順に最大 max_entries 件選ぶ
+    pub fn select(&self, items: &[CuratedItem], default_max: usize) -> Vec<CuratedItem> {
+        items
+            .iter()
+            .filter(|item| item.pinned || item.item.likes_count >= self.min_likes)
+            .take(self.options.max_entries(default_max))
+            .cloned()
+            .collect()
+    }
+}
+
+pub fn validate_tiers(tiers: &[FeedTier], min_likes: u32) -> Result<(), AppError> {
+    let mut names = HashSet::new();
+    for tier in tiers {
+        let name = tier.name.trim();
+        if name.is_empty() {
+            return Err(AppError::config("tiers の name が空です"));
+        }
+        if !names.insert(name) {
+            return Err(AppError::config(format!(
+                "tiers の name が重複しています: {}",
+                name
+            )));
+        }
+        // min_likes 未満の item は state に保存されないため選べない
+        if tier.min_likes < min_likes {
+            return Err(AppError::config(format!(
+                "tiers の min_likes は min_likes ({}) 以上で指定してください: {}",
+                min_likes, name
+            )));
+        }
+        tier.options.validate("tiers", name)?;
+    }
+    Ok(())
+}
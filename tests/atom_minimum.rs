@@ -1,5 +1,9 @@
 use chrono::{DateTime, FixedOffset, Utc};
-use qiita_high_likes_rss::atom::{build_feed_xml, default_feed_updated, FeedEntry, FeedInfo};
+use qiita_high_likes_rss::atom::{
+    build_feed_xml, default_feed_updated, EntrySource, FeedAuthor, FeedEntry, FeedInfo,
+};
+
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
 
 #[test]
 fn atom_minimum_requirements() {
@@ -10,7 +14,11 @@ fn atom_minimum_requirements() {
         title: "Test".to_string(),
         link: "https://qiita.com/test/items/xxx".to_string(),
         updated,
+        published: None,
+        author: None,
         summary_html: "Likes: 1".to_string(),
+        source: None,
+        qiita: None,
     };
 
     let now = Utc::now();
@@ -18,9 +26,13 @@ fn atom_minimum_requirements() {
         id: "https://example.com/feed.xml".to_string(),
         title: "Sample".to_string(),
         description: "Desc".to_string(),
-        updated: default_feed_updated(&[entry.clone()], now),
+        updated: default_feed_updated(std::slice::from_ref(&entry), now),
         feed_url: "https://example.com/feed.xml".to_string(),
         index_url: "https://example.com/index.html".to_string(),
+        lang: None,
+        icon: None,
+        logo: None,
+        rights: None,
         entries: vec![entry],
     };
 
@@ -92,3 +104,112 @@ fn atom_minimum_requirements() {
         .expect("summary missing");
     assert_eq!(summary.attribute("type"), Some("html"));
 }
+
+#[test]
+fn atom_author_published_and_feed_metadata() {
+    let updated: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339("2024-01-02T00:00:00+09:00").unwrap();
+    let published: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339("2024-01-01T00:00:00+09:00").unwrap();
+    let entry = FeedEntry {
+        id: "tag:qiita.com,2024:xxx".to_string(),
+        title: "Test".to_string(),
+        link: "https://qiita.com/test/items/xxx".to_string(),
+        updated,
+        published: Some(published),
+        author: Some(FeedAuthor {
+            name: "Test User".to_string(),
+            uri: Some("https://qiita.com/test".to_string()),
+        }),
+        summary_html: "Likes: 1".to_string(),
+        source: Some(EntrySource {
+            url: "https://qiita.com/popular-items/feed".to_string(),
+            title: Some("Qiita".to_string()),
+        }),
+        qiita: None,
+    };
+    let feed = FeedInfo {
+        id: "https://example.com/feed.xml".to_string(),
+        title: "Sample".to_string(),
+        description: "Desc".to_string(),
+        updated,
+        feed_url: "https://example.com/feed.xml".to_string(),
+        index_url: "https://example.com/index.html".to_string(),
+        lang: Some("ja".to_string()),
+        icon: Some("https://example.com/favicon.png".to_string()),
+        logo: Some("https://example.com/logo.png".to_string()),
+        rights: Some("(c) Sample".to_string()),
+        entries: vec![entry],
+    };
+
+    let xml = build_feed_xml(&feed).expect("feed xml");
+    let doc = roxmltree::Document::parse(&xml).expect("xml parse");
+    let feed_node = doc.root_element();
+    assert_eq!(feed_node.attribute((XML_NS, "lang")), Some("ja"));
+
+    let child_text = |node: roxmltree::Node<'_, '_>, name: &str| {
+        node.children()
+            .find(|n| n.has_tag_name(name))
+            .and_then(|n| n.text())
+            .map(str::to_string)
+    };
+
+    // entry に author が無くても妥当になるよう feed 全体の author を持つ
+    let feed_author = feed_node
+        .children()
+        .find(|n| n.has_tag_name("author"))
+        .expect("feed author missing");
+    assert_eq!(child_text(feed_author, "name").as_deref(), Some("Sample"));
+    assert_eq!(
+        child_text(feed_author, "uri").as_deref(),
+        Some("https://example.com/index.html")
+    );
+
+    let generator = feed_node
+        .children()
+        .find(|n| n.has_tag_name("generator"))
+        .expect("generator missing");
+    assert_eq!(generator.text(), Some("qiita-feed"));
+    assert_eq!(
+        generator.attribute("version"),
+        Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(
+        child_text(feed_node, "icon").as_deref(),
+        Some("https://example.com/favicon.png")
+    );
+    assert_eq!(
+        child_text(feed_node, "logo").as_deref(),
+        Some("https://example.com/logo.png")
+    );
+    assert_eq!(
+        child_text(feed_node, "rights").as_deref(),
+        Some("(c) Sample")
+    );
+
+    let entry_node = feed_node
+        .children()
+        .find(|n| n.has_tag_name("entry"))
+        .expect("entry missing");
+    assert_eq!(
+        child_text(entry_node, "published").as_deref(),
+        Some("2024-01-01T00:00:00+09:00")
+    );
+    let author = entry_node
+        .children()
+        .find(|n| n.has_tag_name("author"))
+        .expect("entry author missing");
+    assert_eq!(child_text(author, "name").as_deref(), Some("Test User"));
+    assert_eq!(
+        child_text(author, "uri").as_deref(),
+        Some("https://qiita.com/test")
+    );
+    let source = entry_node
+        .children()
+        .find(|n| n.has_tag_name("source"))
+        .expect("entry source missing");
+    assert_eq!(
+        child_text(source, "id").as_deref(),
+        Some("https://qiita.com/popular-items/feed")
+    );
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::temp_dir;
+use qiita_high_likes_rss::canonical::{canonicalize_url, extract_item_id, extract_username};
+use qiita_high_likes_rss::state::StateStore;
+use std::fs;
+
+#[test]
+fn urls_differing_only_in_noise_share_a_canonical_form() {
+    let canonical = "https://qiita.com/user/items/abc123";
+    for link in [
+        "https://qiita.com/user/items/abc123",
+        "https://qiita.com/user/items/abc123/",
+        "https://Qiita.COM/user/items/abc123?utm_source=feed&utm_medium=rss",
+        "http://qiita.com:80/user/items/abc123#comments",
+        " https://qiita.com:443/user/items/abc123 ",
+    ] {
+        assert_eq!(canonicalize_url(link), canonical, "link={}", link);
+        assert_eq!(extract_item_id(link).as_deref(), Some("abc123"));
+        assert_eq!(extract_username(link).as_deref(), Some("user"));
+    }
+    assert_eq!(extract_item_id("https://qiita.com/user/items/"), None);
+    assert_eq!(extract_username("https://qiita.com/items/abc123"), None);
+}
+
+#[test]
+fn load_merges_duplicate_items_keeping_richest_data() {
+    let dir = temp_dir("canonical");
+    let path = dir.join("articles.json");
+    let item = |key: &str,
+                item_id: Option<&str>,
+                link: &str,
+                likes: u32,
+                tags: &[&str],
+                first_seen: &str,
+                last_seen: &str| {
+        serde_json::json!({
+            "key": key,
+            "item_id": item_id,
+            "title": format!("title {}", last_seen),
+            "link": link,
+            "summary": null,
+            "published": "2024-01-01T00:00:00+09:00",
+            "updated": null,
+            "author_name": "user",
+            "tags": tags,
+            "likes_count": likes,
+            "first_seen": first_seen,
+            "qualified_at": first_seen,
+            "last_seen": last_seen,
+        })
+    };
+    let state = serde_json::json!({
+        "schema_version": 2,
+        "items": [
+            item("abc123", Some("abc123"), "https://qiita.com/user/items/abc123", 120, &["Rust"], "2024-01-05T00:00:00Z", "2024-02-01T00:00:00Z"),
+            item("https://qiita.com/user/items/abc123?utm_source=x", None, "https://qiita.com/user/items/abc123?utm_source=x", 150, &["CLI"], "2024-01-02T00:00:00Z", "2024-01-20T00:00:00Z"),
+            item("https://example.com/post/", None, "https://example.com/post/", 10, &[], "2024-01-02T00:00:00Z", "2024-01-20T00:00:00Z"),
+            item("https://EXAMPLE.com/post?ref=1", None, "https://EXAMPLE.com/post?ref=1", 12, &[], "2024-01-03T00:00:00Z", "2024-01-21T00:00:00Z"),
+        ],
+        "watchlist": [],
+    });
+    fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+
+    let store = StateStore::load(&path).unwrap();
+    let mut keys: Vec<&String> = store.items.keys().collect();
+    keys.sort();
+    assert_eq!(keys, vec!["abc123", "https://example.com/post"]);
+
+    let merged = &store.items["abc123"];
+    assert_eq!(merged.likes_count, 150);
+    assert_eq!(merged.tags, vec!["Rust".to_string(), "CLI".to_string()]);
+    assert_eq!(merged.first_seen, "2024-01-02T00:00:00Z");
+    assert_eq!(merged.title, "title 2024-02-01T00:00:00Z");
+    assert_eq!(store.items["https://example.com/post"].likes_count, 12);
+    assert_eq!(
+        store
+            .find_key("https://qiita.com/user/items/abc123/")
+            .as_deref(),
+        Some("abc123")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
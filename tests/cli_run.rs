@@ -0,0 +1,69 @@
+mod common;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use std::thread;
+
+const EMPTY_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom"><title>Qiita</title></feed>"#;
+
+/// entry の無い Atom feed を返すだけのローカルサーバ。URL を返す
+fn serve_empty_feed() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/atom+xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                EMPTY_FEED.len(),
+                EMPTY_FEED
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{}/feed", addr)
+}
+
+#[test]
+fn workflow_arguments_run_the_feed() {
+    let dir = common::temp_dir("cli-run");
+    let config = fs::read_to_string("config/config.yaml")
+        .unwrap()
+        .replace(
+            "feed_source: \"https://qiita.com/popular-items/feed\"",
+            &format!("feed_source: \"{}\"", serve_empty_feed()),
+        );
+    let config_path = dir.join("config.yaml");
+    fs::write(&config_path, config).unwrap();
+    let public = dir.join("public");
+
+    // .github/workflows/update-feed.yml と同じ引数で実行する
+    let output = Command::new(env!("CARGO_BIN_EXE_qiita-feed"))
+        .env("NO_PROXY", "127.0.0.1")
+        .env("no_proxy", "127.0.0.1")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--state")
+        .arg(public.join("state/articles.json"))
+        .arg("--out")
+        .arg(public.join("feed.xml"))
+        .arg("--index")
+        .arg(public.join("index.html"))
+        .arg("--last-build")
+        .arg(public.join("last_build.txt"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(public.join("feed.xml").exists());
+    assert!(public.join("state/articles.json").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
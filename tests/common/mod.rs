@@ -0,0 +1,108 @@
+// 結合テストで共有する QiitaItem の組み立て・日時のパース・一時ディレクトリ
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use qiita_high_likes_rss::qiita_api::QiitaItem;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub fn parse(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// 空の一時ディレクトリを作る。同じバイナリのテストが並列に動いても重ならないよう連番を付ける
+pub fn temp_dir(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "qiita-feed-{}-{}-{}",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// テスト用の QiitaItem。既定は https://qiita.com/user/items/<id> の記事で、
+/// 必要な項目だけを上書きする
+pub struct ItemBuilder {
+    item: QiitaItem,
+}
+
+impl ItemBuilder {
+    pub fn new(id: &str) -> Self {
+        Self {
+            item: QiitaItem {
+                item_id: Some(id.to_string()),
+                title: format!("title {}", id),
+                link: format!("https://qiita.com/user/items/{}", id),
+                summary: None,
+                published: Some("2024-01-01T00:00:00+09:00".to_string()),
+                updated: None,
+                author_name: Some("user".to_string()),
+                tags: Vec::new(),
+                likes_count: 0,
+                likes_lower_bound: false,
+                likes_full_scan: false,
+                stocks_count: None,
+            },
+        }
+    }
+
+    /// item_id を持たない外部の記事
+    pub fn external(link: &str) -> Self {
+        let mut builder = Self::new("");
+        builder.item.item_id = None;
+        builder.item.title = "title".to_string();
+        builder.item.link = link.to_string();
+        builder
+    }
+
+    pub fn likes(mut self, likes: u32) -> Self {
+        self.item.likes_count = likes;
+        self
+    }
+
+    pub fn lower_bound(mut self, lower_bound: bool) -> Self {
+        self.item.likes_lower_bound = lower_bound;
+        self
+    }
+
+    pub fn full_scan(mut self, full_scan: bool) -> Self {
+        self.item.likes_full_scan = full_scan;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.item.title = title.to_string();
+        self
+    }
+
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.item.summary = Some(summary.to_string());
+        self
+    }
+
+    pub fn published(mut self, published: &str) -> Self {
+        self.item.published = Some(published.to_string());
+        self
+    }
+
+    pub fn updated(mut self, updated: &str) -> Self {
+        self.item.updated = Some(updated.to_string());
+        self
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.item.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    pub fn build(self) -> QiitaItem {
+        self.item
+    }
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use common::{parse, temp_dir, ItemBuilder};
+use qiita_high_likes_rss::curation::Curation;
+use qiita_high_likes_rss::state::{StateStore, StoredItem};
+use std::fs;
+
+fn stored_items() -> Vec<StoredItem> {
+    let now: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a").likes(100).build(),
+            ItemBuilder::new("b").likes(50).build(),
+            ItemBuilder::new("c").likes(10).build(),
+        ],
+        now,
+    );
+    state.to_sorted_vec()
+}
+
+#[test]
+fn curation_hides_retitles_and_pins_items() {
+    let curation: Curation = serde_yaml::from_str(
+        r#"
+items:
+  - item_id: "a"
+    hide: true
+  - item_id: "https://qiita.com/user/items/b"
+    title: "差し替え"
+    note: "<必読>"
+  - item_id: "c"
+    pin: true
+"#,
+    )
+    .unwrap();
+
+    let items = stored_items();
+    assert!(curation.is_pinned(&items[2]));
+    let curated = curation.apply(items);
+    let keys: Vec<&str> = curated.iter().map(|c| c.item.key.as_str()).collect();
+    assert_eq!(keys, vec!["b", "c"]);
+    assert_eq!(curated[0].item.title, "差し替え");
+    assert_eq!(curated[0].note.as_deref(), Some("<必読>"));
+    assert!(!curated[0].pinned);
+    assert!(curated[1].pinned);
+}
+
+#[test]
+fn curation_pins_are_kept_by_prune() {
+    let curation: Curation =
+        serde_yaml::from_str("items:\n  - item_id: c\n    pin: true\n").unwrap();
+    let seen = parse("2024-02-01T00:00:00Z");
+    let now = seen + Duration::days(90);
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a").likes(100).build(),
+            ItemBuilder::new("c").likes(10).build(),
+        ],
+        seen,
+    );
+
+    state.pinned_keys = curation.pinned_keys(state.items.values());
+    let report = state.prune(now, 60, 1000);
+    let removed: Vec<&str> = report.removed.iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(removed, vec!["a"]);
+    assert!(state.items.contains_key("c"));
+    // state 側の pinned は書き換えない
+    assert!(!state.items["c"].pinned);
+}
+
+#[test]
+fn curation_rejects_conflicting_entries() {
+    let dir = temp_dir("curation");
+    let config = dir.join("config.yaml");
+
+    // 未指定で curation.yaml が無ければ空
+    let empty = Curation::load_for_config(&config, None).unwrap();
+    assert!(empty.items.is_empty());
+    assert!(Curation::load_for_config(&config, Some("missing.yaml")).is_err());
+
+    fs::write(
+        dir.join("curation.yaml"),
+        "items:\n  - item_id: a\n    pin: true\n    hide: true\n",
+    )
+    .unwrap();
+    assert!(Curation::load_for_config(&config, None).is_err());
+
+    fs::write(
+        dir.join("curation.yaml"),
+        "items:\n  - item_id: a\n    pin: true\n  - item_id: a\n    note: x\n",
+    )
+    .unwrap();
+    assert!(Curation::load_for_config(&config, None).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{parse, temp_dir, ItemBuilder};
+use qiita_high_likes_rss::atom::FeedEntry;
+use qiita_high_likes_rss::curation::CuratedItem;
+use qiita_high_likes_rss::digest::{validate_digests, DigestFeed, DigestPeriod, DigestRecord};
+use qiita_high_likes_rss::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use qiita_high_likes_rss::state::StateStore;
+
+fn digest(period: DigestPeriod) -> DigestFeed {
+    DigestFeed {
+        period,
+        top: 2,
+        periods: 3,
+        utc_offset_hours: 9,
+        options: ExtraFeedOptions::default(),
+    }
+}
+
+fn items_qualified_at(entries: &[(&str, u32, &str)]) -> Vec<CuratedItem> {
+    let mut state = StateStore::default();
+    for (id, likes, qualified) in entries {
+        state.merge_from_feed(
+            &[ItemBuilder::new(id)
+                .likes(*likes)
+                .title(&format!("title <{}>", id))
+                .published("2026-10-01T00:00:00+09:00")
+                .build()],
+            parse(qualified),
+        );
+    }
+    state
+        .items
+        .values()
+        .cloned()
+        .map(CuratedItem::plain)
+        .collect()
+}
+
+/// 締まった期間を records に記録してから entry を作る (実行 1 回分)
+fn run_digest(
+    feed: &DigestFeed,
+    items: &[CuratedItem],
+    records: &mut Vec<DigestRecord>,
+    link: &str,
+    now: DateTime<Utc>,
+) -> Vec<FeedEntry> {
+    feed.close_periods(items, records, now);
+    feed.build_entries(records, link, 10)
+}
+
+#[test]
+fn weekly_digest_covers_closed_weeks_only() {
+    let items = items_qualified_at(&[
+        ("a", 100, "2026-10-13T10:00:00+09:00"),
+        ("b", 300, "2026-10-18T23:30:00+09:00"),
+        ("c", 200, "2026-10-14T12:00:00+09:00"),
+        // 集計中の週 (2026-W43) は出さない
+        ("d", 900, "2026-10-19T01:00:00+09:00"),
+        ("e", 50, "2026-10-05T09:00:00+09:00"),
+    ]);
+    let now = parse("2026-10-19T03:00:00+09:00");
+    let weekly = digest(DigestPeriod::Weekly);
+    let mut records = Vec::new();
+    let entries = run_digest(&weekly, &items, &mut records, "https://example.com/", now);
+
+    assert_eq!(entries.len(), 2);
+    let week = &entries[0];
+    assert_eq!(week.id, "tag:qiita.com,2026:digest-2026-W42");
+    assert_eq!(week.updated.to_rfc3339(), "2026-10-19T00:00:00+09:00");
+    // likes 上位 top 件を HTML のリストにする
+    assert_eq!(
+        week.summary_html,
+        "<ol><li><a href=\"https://qiita.com/user/items/b\">title &lt;b&gt;</a> (Likes: 300)</li>\
+         <li><a href=\"https://qiita.com/user/items/c\">title &lt;c&gt;</a> (Likes: 200)</li></ol>"
+    );
+    assert_eq!(entries[1].id, "tag:qiita.com,2026:digest-2026-W41");
+
+    // 期間が締まるまで id は変わらず、締まった後も同じ id のまま
+    let later = parse("2026-10-26T03:00:00+09:00");
+    let entries = run_digest(&weekly, &items, &mut records, "https://example.com/", later);
+    let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "tag:qiita.com,2026:digest-2026-W43",
+            "tag:qiita.com,2026:digest-2026-W42",
+            "tag:qiita.com,2026:digest-2026-W41",
+        ]
+    );
+}
+
+#[test]
+fn daily_digest_uses_the_configured_offset() {
+    let items = items_qualified_at(&[
+        ("a", 100, "2026-10-17T23:30:00+09:00"),
+        ("b", 200, "2026-10-18T00:30:00+09:00"),
+    ]);
+    let now = parse("2026-10-19T00:10:00+09:00");
+
+    let mut records = Vec::new();
+    let daily = digest(DigestPeriod::Daily);
+    let jst = run_digest(&daily, &items, &mut records, "index.html", now);
+    let ids: Vec<&str> = jst.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "tag:qiita.com,2026:digest-2026-10-18",
+            "tag:qiita.com,2026:digest-2026-10-17",
+        ]
+    );
+    // max_entries で新しい期間だけに絞る
+    let limited = DigestFeed {
+        options: ExtraFeedOptions {
+            max_entries: Some(1),
+            ..ExtraFeedOptions::default()
+        },
+        ..digest(DigestPeriod::Daily)
+    };
+    let entries = limited.build_entries(&records, "index.html", 10);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "tag:qiita.com,2026:digest-2026-10-18");
+
+    // UTC では両方とも 10-17 で、10-18 (UTC) はまだ締まっていない
+    let utc = DigestFeed {
+        utc_offset_hours: 0,
+        ..digest(DigestPeriod::Daily)
+    };
+    let entries = run_digest(&utc, &items, &mut Vec::new(), "index.html", now);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "tag:qiita.com,2026:digest-2026-10-17");
+    assert!(entries[0].summary_html.matches("<li>").count() == 2);
+}
+
+#[test]
+fn closed_digests_keep_their_contents() {
+    let items = items_qualified_at(&[
+        ("a", 100, "2026-10-13T10:00:00+09:00"),
+        ("b", 300, "2026-10-14T12:00:00+09:00"),
+    ]);
+    let weekly = digest(DigestPeriod::Weekly);
+    let mut records = Vec::new();
+    let now = parse("2026-10-19T03:00:00+09:00");
+    let before = run_digest(&weekly, &items, &mut records, "index.html", now);
+    assert_eq!(records.len(), 1);
+
+    // 締めた後に likes が変わったり item が消えたりしても、配信済みの内容は変えない
+    let changed = items_qualified_at(&[("a", 900, "2026-10-13T10:00:00+09:00")]);
+    let after = run_digest(
+        &weekly,
+        &changed,
+        &mut records,
+        "index.html",
+        parse("2026-10-20T03:00:00+09:00"),
+    );
+    assert_eq!(after[0].summary_html, before[0].summary_html);
+    assert_eq!(after[0].updated, before[0].updated);
+
+    // 記録はどの保存形式でも state と一緒に残る
+    let dir = temp_dir("digest");
+    let state = StateStore {
+        digests: records.clone(),
+        ..StateStore::default()
+    };
+    for name in ["articles.json", "articles.jsonl", "articles.db"] {
+        let path = dir.join(name);
+        state.save(&path).unwrap();
+        let loaded = StateStore::load(&path).unwrap();
+        assert_eq!(loaded.digests, records, "{}", name);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // periods より古くなった記録は消す
+    run_digest(
+        &weekly,
+        &[],
+        &mut records,
+        "index.html",
+        parse("2026-11-16T03:00:00+09:00"),
+    );
+    assert!(records.is_empty());
+}
+
+#[test]
+fn weekly_ids_follow_the_iso_week_across_years() {
+    // 2029-12-31 (月) から始まる週は 2030-W01
+    let items = items_qualified_at(&[("a", 100, "2030-01-02T10:00:00+09:00")]);
+    let entries = run_digest(
+        &digest(DigestPeriod::Weekly),
+        &items,
+        &mut Vec::new(),
+        "index.html",
+        parse("2030-01-08T03:00:00+09:00"),
+    );
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "tag:qiita.com,2026:digest-2030-W01");
+    assert!(entries[0].title.contains("2030-W01"));
+}
+
+#[test]
+fn digests_are_validated() {
+    assert!(validate_digests(&[digest(DigestPeriod::Weekly)]).is_ok());
+    let zero_top = DigestFeed {
+        top: 0,
+        ..digest(DigestPeriod::Weekly)
+    };
+    assert!(validate_digests(&[zero_top]).is_err());
+    let bad_offset = DigestFeed {
+        utc_offset_hours: 30,
+        ..digest(DigestPeriod::Daily)
+    };
+    assert!(validate_digests(&[bad_offset]).is_err());
+    assert_eq!(
+        digest(DigestPeriod::Daily).output_path(),
+        "feeds/digest-daily.xml"
+    );
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use chrono::Duration;
+use common::{parse, ItemBuilder};
+use qiita_high_likes_rss::entry_id::{stable_entry_id, EntryIdMode};
+use qiita_high_likes_rss::state::StateStore;
+
+#[test]
+fn stable_ids_do_not_depend_on_the_current_year() {
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("abc")
+                .likes(100)
+                .published("2023-12-31T23:00:00+09:00")
+                .build(),
+            ItemBuilder::external("https://example.com/posts/1?utm_source=x")
+                .likes(100)
+                .published("2023-12-31T23:00:00+09:00")
+                .build(),
+            ItemBuilder::external("https://example.com/posts/2")
+                .likes(100)
+                .published("2023-12-31T23:00:00+09:00")
+                .build(),
+        ],
+        parse("2024-12-31T00:00:00Z"),
+    );
+    state.assign_entry_ids(EntryIdMode::Stable, parse("2025-01-01T00:00:00Z"));
+
+    // 公開日 (記事のタイムゾーン) の年を使う
+    let item = &state.items["abc"];
+    assert_eq!(item.entry_id.as_deref(), Some("tag:qiita.com,2023:abc"));
+
+    let ids: Vec<String> = state
+        .items
+        .values()
+        .filter(|i| i.item_id.is_none())
+        .map(stable_entry_id)
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids
+        .iter()
+        .all(|id| id.starts_with("tag:qiita.com,2023:link-")));
+    assert_ne!(ids[0], ids[1], "id の無い item 同士が同じ id になっている");
+
+    // 同じ記事を指す URL は同じ id になる
+    let mut other = StateStore::default();
+    other.merge_from_feed(
+        &[ItemBuilder::external("https://example.com/posts/1")
+            .likes(100)
+            .published("2023-12-31T23:00:00+09:00")
+            .build()],
+        parse("2026-06-01T00:00:00Z"),
+    );
+    let item = other.items.values().next().unwrap();
+    assert!(ids.contains(&stable_entry_id(item)));
+}
+
+#[test]
+fn legacy_mode_freezes_ids_of_existing_items() {
+    let seen = parse("2025-11-01T00:00:00Z");
+    let now = parse("2026-01-02T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("old")
+                .likes(100)
+                .published("2023-12-31T23:00:00+09:00")
+                .build(),
+            ItemBuilder::external("https://example.com/posts/1")
+                .likes(100)
+                .published("2023-12-31T23:00:00+09:00")
+                .build(),
+        ],
+        seen,
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("new")
+            .likes(100)
+            .published("2023-12-31T23:00:00+09:00")
+            .build()],
+        now,
+    );
+
+    assert_eq!(state.assign_entry_ids(EntryIdMode::Legacy, now), 3);
+    // 以前に配信した item はその時点の形式のまま、新しい item は stable
+    assert_eq!(
+        state.items["old"].entry_id.as_deref(),
+        Some("tag:qiita.com,2026:old")
+    );
+    assert_eq!(
+        state.items["new"].entry_id.as_deref(),
+        Some("tag:qiita.com,2023:new")
+    );
+    // item_id の無い item は旧形式でも「unknown」にせず、記事 URL のハッシュを使う
+    let external = &state.items["https://example.com/posts/1"];
+    assert_eq!(external.entry_id, Some(stable_entry_id(external)));
+    assert!(external
+        .entry_id
+        .as_deref()
+        .is_some_and(|id| id.starts_with("tag:qiita.com,2023:link-")));
+
+    // 記録した id は年が変わっても、feed から再取得しても変わらない
+    let later = now + Duration::days(365);
+    state.merge_from_feed(
+        &[ItemBuilder::new("old")
+            .likes(100)
+            .published("2023-12-31T23:00:00+09:00")
+            .build()],
+        later,
+    );
+    assert_eq!(state.assign_entry_ids(EntryIdMode::Stable, later), 0);
+    assert_eq!(
+        state.items["old"].entry_id.as_deref(),
+        Some("tag:qiita.com,2026:old")
+    );
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, FixedOffset};
+use qiita_high_likes_rss::atom::{FeedAuthor, FeedEntry, FeedInfo, QiitaMeta};
+use qiita_high_likes_rss::json_feed::build_json_feed;
+use serde_json::Value;
+
+#[test]
+fn json_feed_includes_qiita_extension() {
+    let updated: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339("2024-01-02T00:00:00+09:00").unwrap();
+    let entry = FeedEntry {
+        id: "tag:qiita.com,2024:xxx".to_string(),
+        title: "Test".to_string(),
+        link: "https://qiita.com/test/items/xxx".to_string(),
+        updated,
+        published: DateTime::parse_from_rfc3339("2024-01-01T00:00:00+09:00").ok(),
+        author: Some(FeedAuthor {
+            name: "Test User".to_string(),
+            uri: Some("https://qiita.com/test".to_string()),
+        }),
+        summary_html: "<p>body</p>".to_string(),
+        source: None,
+        qiita: Some(QiitaMeta {
+            item_id: Some("xxx".to_string()),
+            likes: 120,
+            likes_lower_bound: false,
+            stocks: Some(30),
+            tags: vec!["Rust".to_string(), "CLI".to_string()],
+        }),
+    };
+    let feed = FeedInfo {
+        id: "https://example.com/feed.xml".to_string(),
+        title: "Sample".to_string(),
+        description: "Desc".to_string(),
+        updated,
+        feed_url: "https://example.com/feed.xml".to_string(),
+        index_url: "https://example.com/index.html".to_string(),
+        lang: None,
+        icon: None,
+        logo: None,
+        rights: None,
+        entries: vec![entry],
+    };
+
+    let json = build_json_feed(&feed, "https://example.com/feed.json").expect("json feed");
+    let value: Value = serde_json::from_str(&json).expect("json parse");
+
+    assert_eq!(value["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(value["title"], "Sample");
+    assert_eq!(value["home_page_url"], "https://example.com/index.html");
+    assert_eq!(value["feed_url"], "https://example.com/feed.json");
+
+    let item = &value["items"][0];
+    assert_eq!(item["id"], "tag:qiita.com,2024:xxx");
+    assert_eq!(item["url"], "https://qiita.com/test/items/xxx");
+    assert_eq!(item["content_html"], "<p>body</p>");
+    assert_eq!(item["date_published"], "2024-01-01T00:00:00+09:00");
+    assert_eq!(item["date_modified"], "2024-01-02T00:00:00+09:00");
+    assert_eq!(item["authors"][0]["name"], "Test User");
+    assert_eq!(item["authors"][0]["url"], "https://qiita.com/test");
+    assert_eq!(item["tags"], serde_json::json!(["Rust", "CLI"]));
+
+    let qiita = &item["_qiita"];
+    assert_eq!(qiita["item_id"], "xxx");
+    assert_eq!(qiita["likes"], 120);
+    assert_eq!(qiita["stocks"], 30);
+    assert_eq!(qiita["tags"], serde_json::json!(["Rust", "CLI"]));
+}
@@ -0,0 +1,83 @@
+use qiita_high_likes_rss::error::AppError;
+use qiita_high_likes_rss::qiita_api::{count_likes_pages, LikesCount};
+
+/// likes が total 件ある記事のページを per_page 件ずつ返し、取得したページを記録する
+fn count(
+    total: usize,
+    per_page: u32,
+    max_pages: u32,
+    threshold: Option<u32>,
+) -> (LikesCount, Vec<u32>) {
+    let mut pages = Vec::new();
+    let counted = count_likes_pages(per_page, max_pages, threshold, |page| {
+        pages.push(page);
+        let start = (page as usize - 1) * per_page as usize;
+        Ok(total.saturating_sub(start).min(per_page as usize))
+    })
+    .unwrap();
+    (counted, pages)
+}
+
+#[test]
+fn threshold_mode_stops_once_the_threshold_is_reached() {
+    let (counted, pages) = count(2500, 100, 20, Some(300));
+    assert_eq!(
+        counted,
+        LikesCount {
+            count: 300,
+            lower_bound: true,
+            requests: 3,
+            full_scan: false,
+        }
+    );
+    assert_eq!(pages, vec![1, 2, 3]);
+}
+
+#[test]
+fn threshold_mode_returns_exact_count_below_the_threshold() {
+    let (counted, pages) = count(250, 100, 20, Some(300));
+    assert_eq!(
+        counted,
+        LikesCount {
+            count: 250,
+            lower_bound: false,
+            requests: 3,
+            full_scan: true,
+        }
+    );
+    assert_eq!(pages, vec![1, 2, 3]);
+
+    // 最終ページで threshold に届いた場合も確定値になる
+    let (counted, _) = count(280, 100, 20, Some(250));
+    assert_eq!(counted.count, 280);
+    assert!(!counted.lower_bound);
+}
+
+#[test]
+fn exact_mode_walks_every_page() {
+    let (counted, pages) = count(2500, 100, 30, None);
+    assert_eq!(counted.count, 2500);
+    assert!(!counted.lower_bound);
+    assert_eq!(pages.len(), 26);
+}
+
+#[test]
+fn exhausted_max_pages_is_a_lower_bound() {
+    let (counted, pages) = count(2500, 100, 20, None);
+    assert_eq!(counted.count, 2000);
+    assert!(counted.lower_bound);
+    assert!(counted.full_scan);
+    assert_eq!(pages.len(), 20);
+}
+
+#[test]
+fn page_errors_are_returned() {
+    let result = count_likes_pages(100, 20, Some(300), |page| {
+        if page == 2 {
+            Err(AppError::network("boom"))
+        } else {
+            Ok(100)
+        }
+    });
+    assert!(result.is_err());
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Duration, Utc};
+use qiita_high_likes_rss::history::{likes_growth_since, record_sample, LikesSample};
+
+#[test]
+fn history_is_hourly_then_daily() {
+    let start: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let mut history: Vec<LikesSample> = Vec::new();
+    // 10 日間、30 分ごとに観測する
+    let mut now = start;
+    let mut likes = 0;
+    while now < start + Duration::days(10) {
+        record_sample(&mut history, likes, None, now);
+        likes += 1;
+        now += Duration::minutes(30);
+    }
+    let last = now - Duration::minutes(30);
+
+    // 直近 48 時間は 1 時間に 1 件、それ以前は 1 日に 1 件
+    let hourly_cutoff = last - Duration::hours(48);
+    let hourly = history
+        .iter()
+        .filter(|s| s.at_time().unwrap() >= hourly_cutoff)
+        .count();
+    let daily = history.len() - hourly;
+    assert!((48..=49).contains(&hourly), "hourly={}", hourly);
+    assert!((8..=9).contains(&daily), "daily={}", daily);
+    assert_eq!(history.last().unwrap().likes, likes - 1);
+
+    let growth = likes_growth_since(&history, last - Duration::hours(24)).unwrap();
+    assert_eq!(growth, 48);
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use chrono::Duration;
+use common::{parse, temp_dir, ItemBuilder};
+use qiita_high_likes_rss::curation::CuratedItem;
+use qiita_high_likes_rss::extra_feed::ExtraFeedOptions;
+use qiita_high_likes_rss::merge::{FieldPolicy, MergePolicy};
+use qiita_high_likes_rss::milestone::{detect_milestones, MilestoneFeed, DEFAULT_MILESTONES};
+use qiita_high_likes_rss::qiita_api::LikesCount;
+use qiita_high_likes_rss::state::StateStore;
+
+fn milestone_feed() -> MilestoneFeed {
+    MilestoneFeed {
+        thresholds: DEFAULT_MILESTONES.to_vec(),
+        options: ExtraFeedOptions::default(),
+    }
+}
+
+#[test]
+fn milestones_are_recorded_once_per_crossing() {
+    let start = parse("2026-10-01T00:00:00Z");
+    // likes が下がった値もそのまま保存する
+    let merge_policy = MergePolicy {
+        likes_count: FieldPolicy::Replace,
+        ..MergePolicy::default()
+    };
+    let mut state = StateStore {
+        merge_policy,
+        milestones: DEFAULT_MILESTONES.to_vec(),
+        ..StateStore::default()
+    };
+    // 初めて見た item は以前の likes が分からないので出さない
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(90)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("b")
+                .likes(120)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+        ],
+        start,
+    );
+    assert!(state.items["b"].milestones.is_empty());
+
+    let reports = state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(600)
+            .published("2026-10-01T00:00:00+09:00")
+            .build()],
+        start + Duration::hours(2),
+    );
+    let thresholds: Vec<u32> = state.items["a"]
+        .milestones
+        .iter()
+        .map(|e| e.threshold)
+        .collect();
+    assert_eq!(thresholds, vec![100, 500]);
+    assert_eq!(
+        reports[0]
+            .changes
+            .iter()
+            .filter(|c| c.field == "milestone")
+            .count(),
+        2
+    );
+
+    // 一度下がってから再び超えても記録は増えない
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(480)
+            .published("2026-10-01T00:00:00+09:00")
+            .build()],
+        start + Duration::hours(3),
+    );
+    assert_eq!(state.items["a"].likes_count, 480);
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(1000)
+            .published("2026-10-01T00:00:00+09:00")
+            .build()],
+        start + Duration::hours(4),
+    );
+    let events = &state.items["a"].milestones;
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[1].reached_at,
+        (start + Duration::hours(2)).to_rfc3339()
+    );
+    assert_eq!(events[2].threshold, 1000);
+
+    // 保存して読み直しても残る
+    let dir = temp_dir("milestone");
+    let path = dir.join("articles.json");
+    state.save(&path).unwrap();
+    let loaded = StateStore::load(&path).unwrap();
+    assert_eq!(loaded.items["a"].milestones, state.items["a"].milestones);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn refreshed_likes_record_milestones() {
+    let start = parse("2026-10-01T00:00:00Z");
+    let mut state = StateStore {
+        milestones: DEFAULT_MILESTONES.to_vec(),
+        ..StateStore::default()
+    };
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(400).build()], start);
+
+    // feed から外れた item も再集計で節目を超えれば記録する
+    let refreshed = LikesCount {
+        count: 520,
+        lower_bound: false,
+        requests: 6,
+        full_scan: true,
+    };
+    assert!(state.update_likes("a", refreshed, start + Duration::days(3)));
+    let events = &state.items["a"].milestones;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].threshold, 500);
+    assert_eq!(events[0].likes, 520);
+    assert_eq!(
+        events[0].reached_at,
+        (start + Duration::days(3)).to_rfc3339()
+    );
+
+    // 同じ節目は二度記録しない
+    state.update_likes("a", refreshed, start + Duration::days(4));
+    assert_eq!(state.items["a"].milestones.len(), 1);
+}
+
+#[test]
+fn unknown_previous_likes_do_not_trigger_milestones() {
+    let now = parse("2026-10-01T00:00:00Z");
+    // 確定した以前の値が無ければ、本当に今回超えたのか分からない
+    assert!(detect_milestones(&[100], None, 150, &[], now).is_empty());
+    assert_eq!(detect_milestones(&[100], Some(10), 150, &[], now).len(), 1);
+    assert!(detect_milestones(&[100], Some(100), 150, &[], now).is_empty());
+}
+
+#[test]
+fn threshold_mode_counts_still_record_milestones() {
+    let start = parse("2026-10-01T00:00:00Z");
+    let mut state = StateStore {
+        milestones: DEFAULT_MILESTONES.to_vec(),
+        ..StateStore::default()
+    };
+    let fetch = |id: &str, likes: u32, lower_bound: bool| {
+        ItemBuilder::new(id)
+            .likes(likes)
+            .lower_bound(lower_bound)
+            .published("2026-10-01T00:00:00+09:00")
+            .build()
+    };
+    state.merge_from_feed(&[fetch("a", 90, false), fetch("b", 90, false)], start);
+
+    // 確定値以下の下限値は採らず、確定値のまま残す
+    for hour in 1..=3 {
+        state.merge_from_feed(&[fetch("a", 60, true)], start + Duration::hours(hour));
+    }
+    assert_eq!(state.items["a"].likes_count, 90);
+    assert!(!state.items["a"].likes_lower_bound);
+    state.merge_from_feed(&[fetch("a", 620, false)], start + Duration::hours(4));
+    let thresholds: Vec<u32> = state.items["a"]
+        .milestones
+        .iter()
+        .map(|e| e.threshold)
+        .collect();
+    assert_eq!(thresholds, vec![100, 500]);
+
+    // 確定値 < 節目 <= 下限値 なら確かに超えている
+    state.merge_from_feed(&[fetch("b", 120, true)], start + Duration::hours(1));
+    assert!(state.items["b"].likes_lower_bound);
+    assert_eq!(state.items["b"].milestones.len(), 1);
+    assert_eq!(state.items["b"].milestones[0].threshold, 100);
+    // 下限値の後の確定値は、最後の確定値 (90) から超えた節目を数える
+    let refreshed = LikesCount {
+        count: 620,
+        lower_bound: false,
+        requests: 7,
+        full_scan: true,
+    };
+    assert!(state.update_likes("b", refreshed, start + Duration::hours(2)));
+    let thresholds: Vec<u32> = state.items["b"]
+        .milestones
+        .iter()
+        .map(|e| e.threshold)
+        .collect();
+    assert_eq!(thresholds, vec![100, 500]);
+}
+
+#[test]
+fn promoted_watchlist_items_record_milestones() {
+    let start = parse("2026-10-01T00:00:00Z");
+    let mut state = StateStore {
+        milestones: vec![10, 100],
+        ..StateStore::default()
+    };
+    state.watch_from_feed(
+        &[
+            ItemBuilder::new("a").likes(8).build(),
+            ItemBuilder::new("b").likes(8).build(),
+        ],
+        start,
+    );
+
+    // watchlist で確認した likes から、feed 入りまでに超えた節目を記録する
+    let likes = LikesCount {
+        count: 12,
+        lower_bound: false,
+        requests: 1,
+        full_scan: true,
+    };
+    state.update_watch_likes("a", likes, start + Duration::hours(1));
+    assert_eq!(state.promote_watchlist(10, start + Duration::hours(1)), 1);
+    let events = &state.items["a"].milestones;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].threshold, 10);
+    assert_eq!(events[0].likes, 12);
+
+    // feed に戻ってきて min_likes に達した場合も同じ
+    state.merge_from_feed(
+        &[ItemBuilder::new("b").likes(15).build()],
+        start + Duration::hours(2),
+    );
+    assert_eq!(state.items["b"].milestones.len(), 1);
+    assert_eq!(state.items["b"].milestones[0].threshold, 10);
+}
+
+#[test]
+fn milestone_feed_emits_one_entry_per_event() {
+    let start = parse("2026-10-01T00:00:00Z");
+    let mut state = StateStore {
+        milestones: DEFAULT_MILESTONES.to_vec(),
+        ..StateStore::default()
+    };
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(50)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("b")
+                .likes(400)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+        ],
+        start,
+    );
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(150)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("b")
+                .likes(520)
+                .published("2026-10-01T00:00:00+09:00")
+                .build(),
+        ],
+        start + Duration::hours(1),
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(510)
+            .published("2026-10-01T00:00:00+09:00")
+            .build()],
+        start + Duration::hours(2),
+    );
+    state.assign_entry_ids(Default::default(), start + Duration::hours(2));
+
+    let items: Vec<CuratedItem> = state
+        .items
+        .values()
+        .cloned()
+        .map(CuratedItem::plain)
+        .collect();
+    let entries = milestone_feed().build_entries(&items, 10);
+    let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "tag:qiita.com,2026:a:likes-500",
+            "tag:qiita.com,2026:b:likes-500",
+            "tag:qiita.com,2026:a:likes-100",
+        ]
+    );
+    assert_eq!(entries[0].title, "500 likes: title a");
+    assert_eq!(milestone_feed().build_entries(&items, 1).len(), 1);
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, FixedOffset};
+use qiita_high_likes_rss::atom::{FeedAuthor, FeedEntry, FeedInfo};
+use qiita_high_likes_rss::rss::build_rss_xml;
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+#[test]
+fn rss_minimum_requirements() {
+    let updated: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339("2024-01-02T00:00:00+09:00").unwrap();
+    let published: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339("2024-01-01T00:00:00+09:00").unwrap();
+    let entry = FeedEntry {
+        id: "tag:qiita.com,2024:xxx".to_string(),
+        title: "Test & more".to_string(),
+        link: "https://qiita.com/test/items/xxx".to_string(),
+        updated,
+        published: Some(published),
+        author: Some(FeedAuthor {
+            name: "test".to_string(),
+            uri: Some("https://qiita.com/test".to_string()),
+        }),
+        summary_html: "Likes: 1<br/><p>body</p>".to_string(),
+        source: None,
+        qiita: None,
+    };
+    let feed = FeedInfo {
+        id: "https://example.com/feed.xml".to_string(),
+        title: "Sample".to_string(),
+        description: String::new(),
+        updated,
+        feed_url: "https://example.com/feed.xml".to_string(),
+        index_url: "https://example.com/index.html".to_string(),
+        lang: None,
+        icon: None,
+        logo: None,
+        rights: None,
+        entries: vec![entry],
+    };
+
+    let xml = build_rss_xml(&feed, "https://example.com/rss.xml").expect("rss xml");
+    let doc = roxmltree::Document::parse(&xml).expect("xml parse");
+
+    let rss = doc.root_element();
+    assert!(rss.has_tag_name("rss"));
+    assert_eq!(rss.attribute("version"), Some("2.0"));
+    let channel = rss
+        .children()
+        .find(|n| n.has_tag_name("channel"))
+        .expect("channel missing");
+    let child_text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|n| n.has_tag_name(name) && n.tag_name().namespace().is_none())
+            .and_then(|n| n.text().map(str::to_string))
+    };
+    assert_eq!(child_text(channel, "title").as_deref(), Some("Sample"));
+    assert_eq!(
+        child_text(channel, "link").as_deref(),
+        Some("https://example.com/index.html")
+    );
+    // description が空なら title で埋める
+    assert_eq!(
+        child_text(channel, "description").as_deref(),
+        Some("Sample")
+    );
+
+    let self_link = channel
+        .children()
+        .find(|n| n.has_tag_name((ATOM_NS, "link")))
+        .expect("atom:link missing");
+    assert_eq!(self_link.attribute("rel"), Some("self"));
+    assert_eq!(
+        self_link.attribute("href"),
+        Some("https://example.com/rss.xml")
+    );
+
+    let item = channel
+        .children()
+        .find(|n| n.has_tag_name("item"))
+        .expect("item missing");
+    assert_eq!(child_text(item, "title").as_deref(), Some("Test & more"));
+    let guid = item
+        .children()
+        .find(|n| n.has_tag_name("guid"))
+        .expect("guid missing");
+    assert_eq!(guid.attribute("isPermaLink"), Some("true"));
+    assert_eq!(guid.text(), Some("https://qiita.com/test/items/xxx"));
+
+    let pub_date = child_text(item, "pubDate").expect("pubDate missing");
+    assert_eq!(DateTime::parse_from_rfc2822(&pub_date).unwrap(), published);
+    let creator = item
+        .children()
+        .find(|n| n.has_tag_name((DC_NS, "creator")))
+        .expect("dc:creator missing");
+    assert_eq!(creator.text(), Some("test"));
+    assert_eq!(
+        child_text(item, "description").as_deref(),
+        Some("Likes: 1<br/><p>body</p>")
+    );
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use chrono::Utc;
+use common::{temp_dir, ItemBuilder};
+use qiita_high_likes_rss::fsio::backup_path;
+use qiita_high_likes_rss::state::StateStore;
+use std::fs;
+
+#[test]
+fn corrupt_state_falls_back_to_newest_backup() {
+    let dir = temp_dir("backup");
+    let path = dir.join("articles.json");
+
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(10).build()], Utc::now());
+    state.save_with_backups(&path, 2).unwrap();
+    state.merge_from_feed(&[ItemBuilder::new("b").likes(20).build()], Utc::now());
+    state.save_with_backups(&path, 2).unwrap();
+    state.merge_from_feed(&[ItemBuilder::new("c").likes(30).build()], Utc::now());
+    state.save_with_backups(&path, 2).unwrap();
+
+    assert!(backup_path(&path, 1).exists());
+    assert!(backup_path(&path, 2).exists());
+    assert!(!backup_path(&path, 3).exists());
+
+    // 書き込み途中で落ちたように本体を切り詰める
+    let content = fs::read_to_string(&path).unwrap();
+    fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+    let restored = StateStore::load(&path).expect("restored from backup");
+    assert_eq!(restored.items.len(), 2);
+    assert!(restored.items.contains_key("b"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn structurally_broken_state_falls_back_to_backup() {
+    let dir = temp_dir("backup-structure");
+    let path = dir.join("articles.json");
+
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(10).build()], Utc::now());
+    state.save_with_backups(&path, 2).unwrap();
+    state.save_with_backups(&path, 2).unwrap();
+    assert!(backup_path(&path, 1).exists());
+
+    // JSON としては読めても、形が崩れた state はバックアップから復旧する
+    for broken in [r#"{"items": {}}"#, r#"{"schema_version": "x", "items": []}"#, "[]"] {
+        fs::write(&path, broken).unwrap();
+        let restored = StateStore::load(&path).expect("restored from backup");
+        assert!(restored.items.contains_key("a"), "{}", broken);
+    }
+
+    // 新しいバイナリが書いた state は壊れていないので復旧せずにエラーにする
+    fs::write(&path, r#"{"schema_version": 99, "items": []}"#).unwrap();
+    assert!(StateStore::load(&path).is_err());
+
+    let _ = fs::remove_dir_all(&dir);
+}
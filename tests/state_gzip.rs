@@ -0,0 +1,92 @@
+mod common;
+
+use chrono::Utc;
+use common::{temp_dir, ItemBuilder};
+use flate2::read::GzDecoder;
+use qiita_high_likes_rss::compress::{gzip, write_precompressed, Precompress, GZIP_MAGIC};
+use qiita_high_likes_rss::fsio::backup_path;
+use qiita_high_likes_rss::state::StateStore;
+use qiita_high_likes_rss::storage::JsonlStorage;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+fn is_gzip(path: &Path) -> bool {
+    fs::read(path).unwrap().starts_with(&GZIP_MAGIC)
+}
+
+#[test]
+fn gz_state_is_compressed_and_round_trips() {
+    let dir = temp_dir("gzip");
+
+    // 圧縮が効くように長めの summary を持たせる
+    let summary = "<p>summary</p>".repeat(50);
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a").likes(10).summary(&summary).build(),
+            ItemBuilder::new("b").likes(20).summary(&summary).build(),
+        ],
+        Utc::now(),
+    );
+
+    for (name, backup_name) in [
+        ("articles.json.gz", "articles.bak1.json.gz"),
+        ("articles.jsonl.gz", "articles.bak1.jsonl.gz"),
+    ] {
+        let path = dir.join(name);
+        state.save_with_backups(&path, 2).unwrap();
+        state.save_with_backups(&path, 2).unwrap();
+        assert!(is_gzip(&path), "{}", name);
+        let backup = backup_path(&path, 1);
+        assert_eq!(backup, dir.join(backup_name));
+        assert!(is_gzip(&backup));
+
+        let loaded = StateStore::load(&path).unwrap();
+        assert_eq!(loaded.items.len(), 2);
+        assert_eq!(loaded.items["b"].likes_count, 20);
+    }
+    let volatile = JsonlStorage::volatile_path(&dir.join("articles.jsonl.gz"));
+    assert_eq!(volatile, dir.join("articles.volatile.json.gz"));
+    assert!(is_gzip(&volatile));
+
+    // 拡張子が .json でも中身が gzip なら展開して読み、gzip のまま書き戻す
+    let plain_name = dir.join("articles.json");
+    let json = fs::read(dir.join("articles.json.gz")).unwrap();
+    fs::write(&plain_name, &json).unwrap();
+    let loaded = StateStore::load(&plain_name).unwrap();
+    assert_eq!(loaded.items.len(), 2);
+    loaded.save(&plain_name).unwrap();
+    assert!(is_gzip(&plain_name));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn precompressed_siblings_decode_to_the_original() {
+    let dir = temp_dir("precompress");
+    let path = dir.join("feed.xml");
+    let content = "<feed>".to_string() + &"<entry/>".repeat(100) + "</feed>";
+
+    write_precompressed(
+        &path,
+        content.as_bytes(),
+        &[Precompress::Gzip, Precompress::Brotli],
+    )
+    .unwrap();
+
+    let mut decoded = String::new();
+    GzDecoder::new(fs::File::open(dir.join("feed.xml.gz")).unwrap())
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, content);
+
+    let mut decoded = String::new();
+    brotli::Decompressor::new(fs::File::open(dir.join("feed.xml.br")).unwrap(), 4096)
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, content);
+    assert!(gzip(content.as_bytes()).unwrap().len() < content.len());
+
+    let _ = fs::remove_dir_all(&dir);
+}
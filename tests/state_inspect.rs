@@ -0,0 +1,113 @@
+mod common;
+
+use common::{parse, ItemBuilder};
+use qiita_high_likes_rss::inspect::{compute_stats, export_csv, list_items, ListFilter, ListSort};
+use qiita_high_likes_rss::state::StateStore;
+
+#[test]
+fn list_filters_and_sorts_by_likes() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(10)
+                .summary("summary")
+                .tags(&["Rust"])
+                .build(),
+            ItemBuilder::new("b")
+                .likes(30)
+                .summary("summary")
+                .tags(&["rust", "CLI"])
+                .build(),
+            ItemBuilder::new("c")
+                .likes(20)
+                .summary("summary")
+                .tags(&["Go"])
+                .build(),
+        ],
+        now,
+    );
+
+    let filter = ListFilter {
+        tag: Some("RUST".to_string()),
+        ..ListFilter::default()
+    };
+    let keys: Vec<&str> = list_items(&state, &filter, ListSort::Likes)
+        .iter()
+        .map(|item| item.key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["b", "a"]);
+
+    let filter = ListFilter {
+        min_likes: Some(15),
+        limit: Some(1),
+        ..ListFilter::default()
+    };
+    let keys: Vec<&str> = list_items(&state, &filter, ListSort::Likes)
+        .iter()
+        .map(|item| item.key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["b"]);
+
+    let stats = compute_stats(&state);
+    assert_eq!(stats.items, 3);
+    assert_eq!(stats.likes_total, 60);
+    assert_eq!(stats.likes_median, 20);
+    assert_eq!(stats.top_tags[0].count, 2);
+}
+
+#[test]
+fn csv_export_escapes_quotes_and_commas() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    let mut item = ItemBuilder::new("a")
+        .likes(10)
+        .summary("summary")
+        .tags(&["Rust", "CLI"])
+        .build();
+    item.title = "Rust, \"fast\"".to_string();
+    state.merge_from_feed(&[item], now);
+
+    let items = list_items(&state, &ListFilter::default(), ListSort::Likes);
+    let csv = export_csv(&items);
+    let mut lines = csv.split("\r\n");
+    assert!(lines.next().unwrap().starts_with("key,item_id,title,"));
+    let row = lines.next().unwrap();
+    assert!(row.starts_with("a,a,\"Rust, \"\"fast\"\"\",https://qiita.com/user/items/a,"));
+    assert!(row.ends_with(",Rust;CLI"));
+}
+
+#[test]
+fn merge_items_keeps_richest_data() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(10)
+            .summary("summary")
+            .tags(&["Rust"])
+            .build()],
+        now,
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("b")
+            .likes(50)
+            .summary("summary")
+            .tags(&["CLI"])
+            .build()],
+        parse("2024-01-20T00:00:00Z"),
+    );
+
+    let merged = state.merge_items("a", "b", now).unwrap();
+    assert_eq!(merged.key, "a");
+    assert_eq!(merged.likes_count, 50);
+    assert_eq!(merged.tags, vec!["Rust".to_string(), "CLI".to_string()]);
+    assert_eq!(
+        merged.first_seen_time(),
+        Some(parse("2024-01-20T00:00:00Z"))
+    );
+    assert!(!state.items.contains_key("b"));
+    assert!(state.merge_items("a", "missing", now).is_err());
+    assert!(state.items.contains_key("a"));
+}
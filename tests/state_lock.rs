@@ -0,0 +1,20 @@
+mod common;
+
+use common::temp_dir;
+use qiita_high_likes_rss::state::StateStore;
+use std::fs;
+use std::time::Duration;
+
+#[test]
+fn second_lock_fails_with_distinct_exit_code() {
+    let dir = temp_dir("lock");
+    let path = dir.join("articles.json");
+
+    let first = StateStore::lock(&path, Duration::ZERO).expect("first lock");
+    let err = StateStore::lock(&path, Duration::from_millis(200)).expect_err("already locked");
+    assert_eq!(err.exit_code(), 6);
+
+    drop(first);
+    StateStore::lock(&path, Duration::ZERO).expect("lock after release");
+    let _ = fs::remove_dir_all(&dir);
+}
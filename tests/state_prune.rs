@@ -0,0 +1,147 @@
+mod common;
+
+use chrono::Duration;
+use common::{parse, ItemBuilder};
+use qiita_high_likes_rss::prune::PruneRule;
+use qiita_high_likes_rss::state::StateStore;
+
+#[test]
+fn rules_are_evaluated_in_order_and_reported() {
+    let seen = parse("2024-01-01T00:00:00Z");
+    let now = seen + Duration::days(90);
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("popular")
+                .likes(800)
+                .published("2023-12-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("stale")
+                .likes(20)
+                .published("2023-12-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("pinned")
+                .likes(5)
+                .published("2020-01-01T00:00:00+09:00")
+                .build(),
+            ItemBuilder::new("deleted")
+                .likes(900)
+                .published("2023-12-01T00:00:00+09:00")
+                .build(),
+        ],
+        seen,
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("old")
+            .likes(30)
+            .published("2021-01-01T00:00:00+09:00")
+            .build()],
+        now - Duration::days(1),
+    );
+    assert!(state.set_pinned("pinned", true));
+    assert!(state.mark_removed("deleted", now - Duration::days(2)));
+
+    let rules = vec![
+        PruneRule::Pinned,
+        PruneRule::Removed { grace_days: 1 },
+        PruneRule::LikesTier {
+            min_likes: 500,
+            keep_days: 365,
+        },
+        PruneRule::PublishedAge { max_days: 730 },
+        PruneRule::LastSeen { max_days: 60 },
+    ];
+
+    let plan = state.plan_prune(&rules, 100, now);
+    assert_eq!(state.items.len(), 5);
+    let removed: Vec<(&str, &str)> = plan
+        .removed
+        .iter()
+        .map(|r| (r.key.as_str(), r.rule.as_str()))
+        .collect();
+    assert_eq!(
+        removed,
+        vec![
+            ("deleted", "removed(grace_days=1)"),
+            ("old", "published_age(max_days=730)"),
+            ("stale", "last_seen(max_days=60)"),
+        ]
+    );
+    assert_eq!(plan.counts_by_rule().len(), 3);
+
+    let report = state.prune_with_rules(&rules, 1, now);
+    // ルールが残すと決めた pinned と likes_tier は件数上限でも残る
+    assert_eq!(report.removed.len(), 3);
+    let mut keys: Vec<&String> = state.items.keys().collect();
+    keys.sort();
+    assert_eq!(keys, vec!["pinned", "popular"]);
+}
+
+#[test]
+fn max_items_removes_only_items_no_rule_decided() {
+    let now = parse("2024-03-01T00:00:00Z");
+    let mut state = StateStore::default();
+    for (id, likes, days) in [
+        ("tier", 800, 30),
+        ("a", 10, 20),
+        ("b", 10, 10),
+        ("c", 10, 1),
+    ] {
+        state.merge_from_feed(
+            &[ItemBuilder::new(id).likes(likes).build()],
+            now - Duration::days(days),
+        );
+    }
+    let rules = vec![
+        PruneRule::LikesTier {
+            min_likes: 500,
+            keep_days: 365,
+        },
+        PruneRule::LastSeen { max_days: 60 },
+    ];
+
+    // likes_tier で残す item は last_seen が最も古くても上限の対象にしない
+    let report = state.prune_with_rules(&rules, 2, now);
+    let removed: Vec<(&str, &str)> = report
+        .removed
+        .iter()
+        .map(|r| (r.key.as_str(), r.rule.as_str()))
+        .collect();
+    assert_eq!(removed, vec![("a", "max_items(2)"), ("b", "max_items(2)")]);
+    let mut keys: Vec<&String> = state.items.keys().collect();
+    keys.sort();
+    assert_eq!(keys, vec!["c", "tier"]);
+}
+
+#[test]
+fn default_prune_matches_last_seen_and_max_items() {
+    let now = parse("2024-03-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(10)
+            .published("2024-01-01T00:00:00+09:00")
+            .build()],
+        now - Duration::days(61),
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("b")
+            .likes(10)
+            .published("2024-01-01T00:00:00+09:00")
+            .build()],
+        now - Duration::days(2),
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("c")
+            .likes(10)
+            .published("2024-01-01T00:00:00+09:00")
+            .build()],
+        now - Duration::days(1),
+    );
+
+    let report = state.prune(now, 60, 1);
+    assert_eq!(report.removed[0].key, "a");
+    assert_eq!(report.removed[1].rule, "max_items(1)");
+    assert!(state.items.contains_key("c"));
+    assert_eq!(state.items.len(), 1);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::temp_dir;
+use qiita_high_likes_rss::schema::CURRENT_SCHEMA_VERSION;
+use qiita_high_likes_rss::state::StateStore;
+use std::fs;
+
+const LEGACY_STATE: &str = r#"{
+  "items": [
+    {
+      "key": "abc",
+      "item_id": "abc",
+      "title": "legacy",
+      "link": "https://qiita.com/user/items/abc",
+      "summary": null,
+      "published": "2024-01-01T00:00:00+09:00",
+      "updated": null,
+      "author_name": "user",
+      "likes_count": 12,
+      "last_seen": "2024-01-02T00:00:00+00:00"
+    },
+    {
+      "key": "def",
+      "item_id": "def",
+      "title": "legacy without published",
+      "link": "https://qiita.com/user/items/def",
+      "summary": null,
+      "published": null,
+      "updated": null,
+      "author_name": "user",
+      "likes_count": 34,
+      "last_seen": "2024-01-03T00:00:00+00:00"
+    }
+  ]
+}"#;
+
+#[test]
+fn legacy_state_is_migrated_on_load() {
+    let dir = temp_dir("schema-legacy");
+    let path = dir.join("articles.json");
+    fs::write(&path, LEGACY_STATE).unwrap();
+
+    let state = StateStore::load(&path).expect("legacy state");
+    assert_eq!(state.items["abc"].likes_count, 12);
+    // 既存 item は移行した時点ではなく公開日時に feed 入りしたものとみなす
+    assert_eq!(state.items["abc"].first_seen, "2024-01-01T00:00:00+09:00");
+    assert_eq!(state.items["abc"].qualified_at, "2024-01-01T00:00:00+09:00");
+    assert_eq!(state.items["def"].first_seen, "2024-01-03T00:00:00+00:00");
+    assert_eq!(state.items["def"].qualified_at, "2024-01-03T00:00:00+00:00");
+    state.save(&path).unwrap();
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(
+        saved["schema_version"].as_u64(),
+        Some(CURRENT_SCHEMA_VERSION as u64)
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn newer_state_is_rejected() {
+    let dir = temp_dir("schema-newer");
+    let path = dir.join("articles.json");
+    let newer = format!(
+        r#"{{"schema_version": {}, "items": []}}"#,
+        CURRENT_SCHEMA_VERSION + 1
+    );
+    fs::write(&path, newer).unwrap();
+
+    let err = StateStore::load(&path).expect_err("newer schema must fail");
+    assert!(err.message.contains("新しい"), "{}", err.message);
+    let _ = fs::remove_dir_all(&dir);
+}
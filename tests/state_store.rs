@@ -0,0 +1,294 @@
+mod common;
+
+use chrono::Duration;
+use common::{parse, ItemBuilder};
+use qiita_high_likes_rss::merge::FieldPolicy;
+use qiita_high_likes_rss::qiita_api::LikesCount;
+use qiita_high_likes_rss::state::StateStore;
+use std::collections::HashSet;
+
+#[test]
+fn lower_bound_does_not_overwrite_exact_count() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(120).build()], now);
+
+    let partial = ItemBuilder::new("a").likes(100).lower_bound(true).build();
+    state.merge_from_feed(&[partial], now + Duration::hours(1));
+
+    // 採らなかった下限値で確定値を下限値扱いにしない
+    let stored = &state.items["a"];
+    assert_eq!(stored.likes_count, 120);
+    assert!(!stored.likes_lower_bound);
+    assert!(!state.exact_recount_due("a", now + Duration::hours(2), 24));
+    assert!(state.exact_recount_due("a", now + Duration::hours(24), 24));
+}
+
+#[test]
+fn capped_recount_is_recorded_as_counted() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    // threshold で打ち切った値しか無いうちは数え直しが必要
+    let partial = ItemBuilder::new("a").likes(300).lower_bound(true).build();
+    state.merge_from_feed(&[partial], now);
+    assert!(state.exact_recount_due("a", now, 24));
+
+    // max_pages で頭打ちでも数え直しを試みた時刻は残し、毎回数え直さない
+    let capped = ItemBuilder::new("a")
+        .likes(2000)
+        .lower_bound(true)
+        .full_scan(true)
+        .build();
+    state.merge_from_feed(&[capped], now + Duration::hours(1));
+    let stored = &state.items["a"];
+    assert_eq!(stored.likes_count, 2000);
+    assert!(stored.likes_lower_bound);
+    assert!(!state.exact_recount_due("a", now + Duration::hours(2), 24));
+    assert!(state.exact_recount_due("a", now + Duration::hours(25), 24));
+
+    let likes = LikesCount {
+        count: 2000,
+        lower_bound: true,
+        requests: 20,
+        full_scan: true,
+    };
+    assert!(state.update_likes("a", likes, now + Duration::hours(25)));
+    assert!(!state.exact_recount_due("a", now + Duration::hours(26), 24));
+}
+
+#[test]
+fn refresh_rotates_oldest_checked_items_first() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(10).build()], now);
+    state.merge_from_feed(
+        &[ItemBuilder::new("b").likes(10).build()],
+        now - Duration::hours(3),
+    );
+    state.merge_from_feed(
+        &[ItemBuilder::new("c").likes(10).build()],
+        now - Duration::hours(1),
+    );
+
+    let exclude: HashSet<String> = ["c".to_string()].into_iter().collect();
+    let keys: Vec<String> = state
+        .refresh_candidates(&exclude)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, vec!["b".to_string(), "a".to_string()]);
+
+    let likes = LikesCount {
+        count: 42,
+        lower_bound: false,
+        requests: 1,
+        full_scan: true,
+    };
+    assert!(state.update_likes("b", likes, now + Duration::hours(1)));
+    assert_eq!(state.items["b"].likes_count, 42);
+    assert_eq!(state.refresh_candidates(&exclude)[0].0, "a");
+}
+
+#[test]
+fn refreshed_likes_follow_the_merge_policy() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let exact = |count| LikesCount {
+        count,
+        lower_bound: false,
+        requests: 1,
+        full_scan: true,
+    };
+    // 既定の max では減った値で上書きしない
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(120).build()], now);
+    assert!(state.update_likes("a", exact(110), now + Duration::hours(1)));
+    assert_eq!(state.items["a"].likes_count, 120);
+
+    state.merge_policy.likes_count = FieldPolicy::Replace;
+    assert!(state.update_likes("a", exact(110), now + Duration::hours(2)));
+    assert_eq!(state.items["a"].likes_count, 110);
+}
+
+#[test]
+fn watchlist_items_are_promoted_once_they_qualify() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.watch_from_feed(
+        &[
+            ItemBuilder::new("a").likes(3).build(),
+            ItemBuilder::new("b").likes(8).build(),
+        ],
+        now,
+    );
+    assert_eq!(state.watchlist.len(), 2);
+
+    let likes = LikesCount {
+        count: 10,
+        lower_bound: true,
+        requests: 1,
+        full_scan: false,
+    };
+    state.update_watch_likes("a", likes, now + Duration::hours(1));
+    assert_eq!(state.promote_watchlist(10, now + Duration::hours(1)), 1);
+    assert!(state.items.contains_key("a"));
+    assert!(!state.watchlist.contains_key("a"));
+    let promoted = &state.items["a"];
+    assert_eq!(promoted.first_seen_time(), Some(now));
+    assert_eq!(promoted.qualified_time(), Some(now + Duration::hours(1)));
+
+    state.prune_watchlist(now + Duration::days(8), 7);
+    assert!(state.watchlist.is_empty());
+}
+
+#[test]
+fn watchlist_rechecks_oldest_checked_items_first() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.watch_from_feed(
+        &[
+            ItemBuilder::new("a").likes(3).build(),
+            ItemBuilder::new("b").likes(3).build(),
+            ItemBuilder::new("c").likes(3).build(),
+        ],
+        now,
+    );
+    let likes = LikesCount {
+        count: 4,
+        lower_bound: false,
+        requests: 1,
+        full_scan: true,
+    };
+    state.update_watch_likes("a", likes, now + Duration::hours(1));
+
+    // 予算で打ち切られても、次の実行では確認していない候補から回る
+    let exclude: HashSet<String> = ["c".to_string()].into_iter().collect();
+    let keys: Vec<String> = state
+        .watch_candidates(&exclude)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, vec!["b".to_string(), "a".to_string()]);
+}
+
+#[test]
+fn first_seen_and_qualified_at_survive_merges() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(10).build()], now);
+    state.merge_from_feed(
+        &[ItemBuilder::new("a").likes(30).build()],
+        now + Duration::days(1),
+    );
+
+    let stored = &state.items["a"];
+    assert_eq!(stored.first_seen_time(), Some(now));
+    assert_eq!(stored.qualified_time(), Some(now));
+    assert_eq!(stored.likes_count, 30);
+}
+
+#[test]
+fn merge_keeps_missing_fields_and_reports_changes() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[ItemBuilder::new("a").likes(50).summary("summary").build()],
+        now,
+    );
+
+    let mut incoming = ItemBuilder::new("a").likes(40).summary("summary").build();
+    incoming.title = "renamed".to_string();
+    incoming.summary = None;
+    incoming.author_name = None;
+    let reports = state.merge_from_feed(&[incoming], now + Duration::hours(1));
+
+    let stored = &state.items["a"];
+    assert_eq!(stored.title, "renamed");
+    assert_eq!(stored.summary.as_deref(), Some("summary"));
+    assert_eq!(stored.author_name.as_deref(), Some("user"));
+    assert_eq!(stored.likes_count, 50);
+
+    assert_eq!(reports.len(), 1);
+    assert!(!reports[0].is_new);
+    let fields: Vec<&str> = reports[0].changes.iter().map(|c| c.field).collect();
+    assert_eq!(fields, vec!["title", "edited_at"]);
+
+    state.merge_policy.likes_count = FieldPolicy::Replace;
+    state.merge_from_feed(
+        &[ItemBuilder::new("a").likes(40).summary("summary").build()],
+        now + Duration::hours(2),
+    );
+    assert_eq!(state.items["a"].likes_count, 40);
+}
+
+#[test]
+fn content_edits_are_detected_by_hash() {
+    let now = parse("2024-02-01T00:00:00Z");
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[ItemBuilder::new("a").likes(10).summary("summary").build()],
+        now,
+    );
+    let hash = state.items["a"].content_hash.clone();
+    assert!(hash.is_some());
+    assert_eq!(state.items["a"].edited_at, None);
+
+    // 空白だけの違いや updated の変化は編集とみなさない
+    let trivial = ItemBuilder::new("a")
+        .likes(11)
+        .summary("  summary ")
+        .updated("2024-01-05T00:00:00+09:00")
+        .build();
+    state.merge_from_feed(&[trivial], now + Duration::hours(1));
+    assert_eq!(state.items["a"].content_hash, hash);
+    assert_eq!(state.items["a"].edited_at, None);
+
+    let edited = ItemBuilder::new("a")
+        .likes(12)
+        .summary("rewritten summary")
+        .build();
+    let reports = state.merge_from_feed(&[edited], now + Duration::hours(2));
+    assert_ne!(state.items["a"].content_hash, hash);
+    let edited_at = (now + Duration::hours(2)).to_rfc3339();
+    assert_eq!(state.items["a"].edited_at.as_deref(), Some(edited_at.as_str()));
+    assert!(reports[0].changes.iter().any(|c| c.field == "edited_at"));
+}
+
+#[test]
+fn likes_history_is_hourly_for_two_days_then_daily() {
+    let mut state = StateStore::default();
+    let times = [
+        "2024-02-07T01:00:00Z",
+        "2024-02-07T05:00:00Z",
+        "2024-02-08T11:30:00Z",
+        "2024-02-08T11:50:00Z",
+        // 最後の観測からちょうど 48 時間前は 1 時間ごとの側に入る
+        "2024-02-08T12:00:00Z",
+        "2024-02-08T12:20:00Z",
+        "2024-02-10T11:10:00Z",
+        "2024-02-10T11:40:00Z",
+        "2024-02-10T12:00:00Z",
+    ];
+    state.merge_from_feed(&[ItemBuilder::new("a").likes(1).build()], parse(times[0]));
+    for (likes, at) in times.iter().enumerate().skip(1) {
+        let likes = LikesCount {
+            count: likes as u32 + 1,
+            lower_bound: false,
+            requests: 1,
+            full_scan: true,
+        };
+        assert!(state.update_likes("a", likes, parse(at)));
+    }
+
+    // 48 時間より前は 1 日、以降は 1 時間の中で最新の 1 件だけが残る
+    let kept: Vec<(String, u32)> = state
+        .likes_history("a")
+        .unwrap()
+        .iter()
+        .map(|s| (s.at_time().unwrap().to_rfc3339(), s.likes))
+        .collect();
+    let expected: Vec<(String, u32)> = [(1, 2), (3, 4), (5, 6), (7, 8), (8, 9)]
+        .iter()
+        .map(|&(i, likes)| (parse(times[i]).to_rfc3339(), likes))
+        .collect();
+    assert_eq!(kept, expected);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use common::{temp_dir, ItemBuilder};
+use qiita_high_likes_rss::state::StateStore;
+use qiita_high_likes_rss::storage::JsonlStorage;
+use std::fs;
+
+#[test]
+fn jsonl_keeps_volatile_fields_out_of_item_lines() {
+    let dir = temp_dir("jsonl");
+    let path = dir.join("articles.jsonl");
+
+    let now: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("b")
+                .likes(20)
+                .summary("<p>body</p>")
+                .tags(&["Rust"])
+                .build(),
+            ItemBuilder::new("a")
+                .likes(10)
+                .summary("<p>body</p>")
+                .tags(&["Rust"])
+                .build(),
+        ],
+        now,
+    );
+    state.save(&path).unwrap();
+    let first = fs::read_to_string(&path).unwrap();
+
+    let lines: Vec<&str> = first.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("\"key\":\"a\""));
+    assert!(!first.contains("likes_count"));
+    assert!(!first.contains("last_seen"));
+
+    // likes だけが変わっても item 行は変わらない
+    state.merge_from_feed(
+        &[ItemBuilder::new("a")
+            .likes(15)
+            .summary("<p>body</p>")
+            .tags(&["Rust"])
+            .build()],
+        now + Duration::hours(1),
+    );
+    state.save(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), first);
+    let volatile = fs::read_to_string(JsonlStorage::volatile_path(&path)).unwrap();
+    assert!(volatile.contains("\"likes_count\": 15"));
+
+    let loaded = StateStore::load(&path).unwrap();
+    assert_eq!(loaded.items["a"].likes_count, 15);
+    assert_eq!(loaded.items["b"].tags, vec!["Rust"]);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{temp_dir, ItemBuilder};
+use qiita_high_likes_rss::state::StateStore;
+use qiita_high_likes_rss::storage::{migrate_state, SqliteStorage};
+use std::fs;
+
+#[test]
+fn migrate_json_to_sqlite_and_query() {
+    let dir = temp_dir("sqlite");
+    let json_path = dir.join("articles.json");
+    let db_path = dir.join("articles.db");
+
+    let now: DateTime<Utc> = Utc::now();
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(10)
+                .published("2024-01-01T09:00:00+09:00")
+                .tags(&["Rust"])
+                .build(),
+            ItemBuilder::new("b")
+                .likes(50)
+                .published("2024-01-02T00:00:00Z")
+                .tags(&["Python", "rust"])
+                .build(),
+            ItemBuilder::new("c")
+                .likes(5)
+                .published("2024-01-03T00:00:00Z")
+                .tags(&["Go"])
+                .build(),
+        ],
+        now,
+    );
+    state.save(&json_path).unwrap();
+
+    assert_eq!(migrate_state(&json_path, &db_path).unwrap(), 3);
+    let loaded = StateStore::load(&db_path).unwrap();
+    assert_eq!(loaded.items.len(), 3);
+    assert_eq!(loaded.items["b"].tags, vec!["Python", "rust"]);
+
+    let storage = SqliteStorage::new(&db_path);
+    let popular: Vec<String> = storage
+        .items_by_min_likes(10)
+        .unwrap()
+        .into_iter()
+        .map(|item| item.key)
+        .collect();
+    assert_eq!(popular, vec!["b", "a"]);
+
+    let tagged: Vec<String> = storage
+        .items_by_tag("RUST")
+        .unwrap()
+        .into_iter()
+        .map(|item| item.key)
+        .collect();
+    assert_eq!(tagged, vec!["b", "a"]);
+
+    let from = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let to = DateTime::parse_from_rfc3339("2024-01-02T12:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let ranged: Vec<String> = storage
+        .items_published_between(from, to)
+        .unwrap()
+        .into_iter()
+        .map(|item| item.key)
+        .collect();
+    assert_eq!(ranged, vec!["a", "b"]);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::ItemBuilder;
+use qiita_high_likes_rss::curation::CuratedItem;
+use qiita_high_likes_rss::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use qiita_high_likes_rss::html::{build_index_html, FeedLink, IndexPage};
+use qiita_high_likes_rss::state::StateStore;
+use qiita_high_likes_rss::tag_feed::{tag_slug, validate_tag_feeds, TagFeed};
+
+fn tag_feed(tag: &str) -> TagFeed {
+    TagFeed {
+        tag: tag.to_string(),
+        options: ExtraFeedOptions::default(),
+    }
+}
+
+#[test]
+fn tag_feeds_select_matching_items_in_order() {
+    let now: DateTime<Utc> = Utc::now();
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a")
+                .likes(300)
+                .tags(&["Rust", "CLI"])
+                .build(),
+            ItemBuilder::new("b").likes(200).tags(&["React"]).build(),
+            ItemBuilder::new("c").likes(100).tags(&["rust"]).build(),
+            ItemBuilder::new("d").likes(50).tags(&["RUST"]).build(),
+        ],
+        now,
+    );
+    let mut items: Vec<CuratedItem> = state
+        .items
+        .values()
+        .cloned()
+        .map(CuratedItem::plain)
+        .collect();
+    items.sort_by_key(|i| std::cmp::Reverse(i.item.likes_count));
+
+    let rust = tag_feed("rust");
+    let selected: Vec<String> = rust
+        .select(&items, 10)
+        .into_iter()
+        .map(|i| i.item.key)
+        .collect();
+    assert_eq!(selected, vec!["a", "c", "d"]);
+
+    let limited = TagFeed {
+        options: ExtraFeedOptions {
+            max_entries: Some(2),
+            ..ExtraFeedOptions::default()
+        },
+        ..tag_feed("rust")
+    };
+    assert_eq!(limited.select(&items, 10).len(), 2);
+    assert_eq!(rust.select(&items, 1).len(), 1);
+
+    assert_eq!(rust.output_path(), "feeds/tags/rust.xml");
+    assert_eq!(rust.feed_title("Qiita"), "Qiita #rust");
+}
+
+#[test]
+fn tag_slugs_and_validation() {
+    assert_eq!(tag_slug("Rust"), "rust");
+    assert_eq!(tag_slug("Next.js"), "next.js");
+    assert_eq!(tag_slug("C++"), "c");
+    assert_eq!(tag_slug("機械学習"), "機械学習");
+    assert_eq!(tag_slug("  "), "");
+
+    assert!(validate_tag_feeds(&[tag_feed("rust"), tag_feed("react")]).is_ok());
+    assert!(validate_tag_feeds(&[tag_feed("+")]).is_err());
+    let explicit = TagFeed {
+        options: ExtraFeedOptions {
+            path: Some("feeds/cpp.xml".to_string()),
+            ..ExtraFeedOptions::default()
+        },
+        ..tag_feed("+")
+    };
+    assert!(validate_tag_feeds(&[explicit]).is_ok());
+    let empty = TagFeed {
+        options: ExtraFeedOptions {
+            max_entries: Some(0),
+            ..ExtraFeedOptions::default()
+        },
+        ..tag_feed("rust")
+    };
+    assert!(validate_tag_feeds(&[empty]).is_err());
+}
+
+#[test]
+fn index_links_extra_feeds() {
+    let page = IndexPage {
+        title: "Sample".to_string(),
+        description: "Desc".to_string(),
+        feed_url: "https://example.com/feed.xml".to_string(),
+        updated: DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap(),
+        min_likes: 10,
+        feed_source: "https://qiita.com/popular-items/feed".to_string(),
+        extra_feeds: vec![FeedLink {
+            title: "Sample #rust & cli".to_string(),
+            url: "https://example.com/feeds/tags/rust.xml".to_string(),
+        }],
+    };
+    let html = build_index_html(&page);
+    assert!(html.contains(
+        "<li><a href=\"https://example.com/feeds/tags/rust.xml\">Sample #rust &amp; cli</a></li>"
+    ));
+}
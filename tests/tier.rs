@@ -0,0 +1,92 @@
+mod common;
+
+use chrono::Utc;
+use common::{temp_dir, ItemBuilder};
+use qiita_high_likes_rss::config::AppConfig;
+use qiita_high_likes_rss::curation::CuratedItem;
+use qiita_high_likes_rss::extra_feed::{ExtraFeed, ExtraFeedOptions};
+use qiita_high_likes_rss::state::StateStore;
+use qiita_high_likes_rss::tier::{validate_tiers, FeedTier};
+use std::fs;
+
+fn tier(name: &str, min_likes: u32) -> FeedTier {
+    FeedTier {
+        name: name.to_string(),
+        min_likes,
+        options: ExtraFeedOptions::default(),
+    }
+}
+
+#[test]
+fn tiers_select_items_above_their_threshold() {
+    let mut state = StateStore::default();
+    state.merge_from_feed(
+        &[
+            ItemBuilder::new("a").likes(300).build(),
+            ItemBuilder::new("b").likes(120).build(),
+            ItemBuilder::new("c").likes(60).build(),
+            ItemBuilder::new("d").likes(10).build(),
+        ],
+        Utc::now(),
+    );
+    let mut items: Vec<CuratedItem> = state
+        .items
+        .values()
+        .cloned()
+        .map(CuratedItem::plain)
+        .collect();
+    items.sort_by_key(|i| std::cmp::Reverse(i.item.likes_count));
+    // pin した item はしきい値に関係なく載せる
+    let pinned = items.iter().position(|i| i.item.key == "d").unwrap();
+    items[pinned].pinned = true;
+
+    let hundred = tier("100+", 100);
+    let selected: Vec<String> = hundred
+        .select(&items, 10)
+        .into_iter()
+        .map(|i| i.item.key)
+        .collect();
+    assert_eq!(selected, vec!["a", "b", "d"]);
+    assert_eq!(tier("50+", 50).select(&items, 2).len(), 2);
+
+    assert_eq!(hundred.output_path(), "feed-100.xml");
+    assert_eq!(hundred.feed_title("Qiita"), "Qiita (100+ likes)");
+}
+
+#[test]
+fn tiers_are_validated() {
+    assert!(validate_tiers(&[tier("50+", 50), tier("100+", 100)], 10).is_ok());
+    assert!(validate_tiers(&[tier("5+", 5)], 10).is_err());
+    assert!(validate_tiers(&[tier("a", 50), tier("a", 100)], 10).is_err());
+    assert!(validate_tiers(&[tier(" ", 50)], 10).is_err());
+}
+
+#[test]
+fn config_rejects_overlapping_output_paths() {
+    let dir = temp_dir("tier");
+    let path = dir.join("config.yaml");
+    let base: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string("config/config.yaml").unwrap()).unwrap();
+
+    let write = |tiers: &str, tag_feeds: &str| {
+        let mut value = base.clone();
+        value["tiers"] = serde_yaml::from_str(tiers).unwrap();
+        value["tag_feeds"] = serde_yaml::from_str(tag_feeds).unwrap();
+        fs::write(&path, serde_yaml::to_string(&value).unwrap()).unwrap();
+    };
+
+    write(
+        "[{name: '50+', min_likes: 50}, {name: '100+', min_likes: 100}]",
+        "[{tag: rust}]",
+    );
+    let config = AppConfig::load(&path).expect("config");
+    assert_eq!(config.tiers.len(), 2);
+
+    write("[{name: '100+', min_likes: 100, path: feed.xml}]", "[]");
+    assert!(AppConfig::load(&path).is_err());
+
+    write("[]", "[{tag: rust}, {tag: Rust}]");
+    assert!(AppConfig::load(&path).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}